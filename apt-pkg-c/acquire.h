@@ -2,6 +2,8 @@
 #include <apt-pkg/acquire-item.h>
 #include <apt-pkg/acquire-worker.h>
 #include <apt-pkg/acquire.h>
+#include <apt-pkg/fileutl.h>
+#include <apt-pkg/hashes.h>
 #include <iostream>
 #include <memory>
 #include "rust/cxx.h"
@@ -9,6 +11,7 @@
 #include "rust-apt/src/progress.rs"
 
 #include "types.h"
+#include "util.h"
 
 // ItemState Enum
 using ItemState = pkgAcquire::Item::ItemState;
@@ -53,6 +56,22 @@ struct ItemDesc {
 	String description() const { return ptr->Description; }
 	String short_desc() const { return ptr->ShortDesc; }
 
+	/// The category of the item. The values must match AcqItemType in acquire.rs
+	u8 item_type() const {
+		pkgAcquire::Item* item = ptr->Owner;
+		if (dynamic_cast<pkgAcqArchive*>(item)) { return 4; }
+		if (dynamic_cast<pkgAcqChangelog*>(item)) { return 5; }
+		if (dynamic_cast<pkgAcqFile*>(item)) { return 6; }
+
+		// The index and release items are not exported by libapt-pkg,
+		// but their short descriptions come straight from the IndexTarget.
+		const std::string& desc = ptr->ShortDesc;
+		if (desc == "InRelease" || desc == "Release") { return 0; }
+		if (desc == "Release.gpg") { return 1; }
+		if (desc.rfind("Translation-", 0) == 0) { return 3; }
+		return 2;
+	}
+
 	UniquePtr<Item> owner() const { return std::make_unique<Item>(ptr->Owner); }
 
 	// Cast away the constness in this case. We aren't going to change it.
@@ -226,3 +245,29 @@ inline UniquePtr<std::vector<AcqWorker>> PkgAcquire::workers() const {
 
 inline UniquePtr<AcqTextStatus> acquire_status() { return std::make_unique<AcqTextStatus>(); }
 inline UniquePtr<PkgAcquire> create_acquire() { return std::make_unique<PkgAcquire>(); }
+
+/// Download a single uri into dest_dir without checking any hashes.
+///
+/// Returns the path of the downloaded file.
+inline String download_file(str uri, str dest_dir, AcqTextStatus& progress) {
+	pkgAcquire acquire(&progress);
+	std::string uri_str(uri);
+	std::string filename = flNotDir(uri_str);
+	std::string dest = flCombine(std::string(dest_dir), filename);
+
+	// The item is owned and cleaned up by pkgAcquire.
+	new pkgAcqFile(&acquire, uri_str, HashStringList(), 0, uri_str, filename, "", dest);
+
+	acquire.Run(progress.callback->pulse_interval());
+
+	for (auto item = acquire.ItemsBegin(); item != acquire.ItemsEnd(); ++item) {
+		if ((*item)->Status != pkgAcquire::Item::StatDone) {
+			_error->Error(
+				"Failed to fetch %s  %s", (*item)->DescURI().c_str(), (*item)->ErrorText.c_str()
+			);
+		}
+	}
+
+	handle_errors();
+	return dest;
+}
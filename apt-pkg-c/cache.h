@@ -4,6 +4,7 @@
 #include <apt-pkg/error.h>
 #include <apt-pkg/fileutl.h>
 #include <apt-pkg/indexfile.h>
+#include <apt-pkg/metaindex.h>
 #include <apt-pkg/pkgcache.h>
 #include <apt-pkg/policy.h>
 #include <apt-pkg/sourcelist.h>
@@ -67,6 +68,37 @@ struct PkgCacheFile : public pkgCacheFile {
 		return std::make_unique<IndexFile>(index);
 	}
 
+	/// The uncompressed URIs of the Translation files of the release
+	/// and component that `file` belongs to.
+	///
+	/// These come from the index targets of the release,
+	/// so they follow `Acquire::Languages` and work for flat repositories.
+	Vec<String> translation_uris(const PkgFileIterator& file) const {
+		Vec<String> uris;
+		pkgCache& cache = *this->unconst()->GetPkgCache();
+		std::string component = file.Component() == nullptr ? "" : file.Component();
+
+		for (metaIndex* meta : *this->unconst()->GetSourceList()) {
+			bool found = false;
+			for (pkgIndexFile* index : *meta->GetIndexFiles()) {
+				if (index->FindInCache(cache) == file) {
+					found = true;
+					break;
+				}
+			}
+
+			if (!found) { continue; }
+
+			for (IndexTarget const& target : meta->GetIndexTargets()) {
+				if (target.Option(IndexTarget::CREATED_BY) == "Translations" &&
+					target.Option(IndexTarget::COMPONENT) == component) {
+					uris.push_back(target.URI);
+				}
+			}
+		}
+		return uris;
+	}
+
 	bool get_indexes(const PkgAcquire& fetcher) const {
 		return this->unconst()->GetSourceList()->GetIndexes(fetcher.ptr, true);
 	}
@@ -59,6 +59,16 @@ struct PkgFileIterator : public pkgCache::PkgFileIterator {
 
 	bool is_downloadable() const { return !this->Flagged(pkgCache::Flag::NotSource); }
 
+	bool is_not_automatic() const {
+		pkgCache::RlsFileIterator release = this->ReleaseFile();
+		return !release.end() && release.Flagged(pkgCache::Flag::NotAutomatic);
+	}
+
+	bool is_but_automatic_upgrades() const {
+		pkgCache::RlsFileIterator release = this->ReleaseFile();
+		return !release.end() && release.Flagged(pkgCache::Flag::ButAutomaticUpgrades);
+	}
+
 	UniquePtr<PkgFileIterator> unique() const { return std::make_unique<PkgFileIterator>(*this); }
 
 	PkgFileIterator(const pkgCache::PkgFileIterator& base) : pkgCache::PkgFileIterator(base) {};
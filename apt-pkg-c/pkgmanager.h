@@ -7,6 +7,7 @@
 #include <apt-pkg/pkgsystem.h>
 #include <apt-pkg/sourcelist.h>
 #include <memory>
+#include <vector>
 
 #include "cache.h"
 #include "rust-apt/src/progress.rs"
@@ -61,6 +62,68 @@ struct PackageManager {
 	PackageManager(pkgDepCache* depcache) : pkgmanager(_system->CreatePM(depcache)) {};
 };
 
+/// A package manager that records the actions it would take
+/// in dpkg processing order instead of running dpkg.
+///
+/// Action values are 0 = Unpack, 1 = Configure, 2 = Remove, 3 = Purge.
+struct ActionRecorder : public pkgPackageManager {
+	std::vector<std::pair<pkgCache::PkgIterator, u8>> actions;
+
+	ActionRecorder(pkgDepCache* depcache) : pkgPackageManager(depcache) {};
+
+	bool Install(pkgCache::PkgIterator pkg, std::string) override {
+		actions.emplace_back(pkg, 0);
+		return true;
+	}
+
+	bool Configure(pkgCache::PkgIterator pkg) override {
+		actions.emplace_back(pkg, 1);
+		return true;
+	}
+
+	bool Remove(pkgCache::PkgIterator pkg, bool purge) override {
+		actions.emplace_back(pkg, purge ? 3 : 2);
+		return true;
+	}
+};
+
+struct PkgActions {
+	std::vector<std::pair<pkgCache::PkgIterator, u8>> actions;
+
+	size_t len() const { return actions.size(); }
+
+	UniquePtr<PkgIterator> pkg(size_t index) const {
+		return std::make_unique<PkgIterator>(actions.at(index).first);
+	}
+
+	u8 action(size_t index) const { return actions.at(index).second; }
+};
+
+/// Order the marked changes the way DoInstall would, without running dpkg.
+UniquePtr<PkgActions> ordered_actions(
+	const PkgCacheFile& cache,
+	const PkgDepCache& depcache,
+	const PkgRecords& records
+) {
+	ActionRecorder recorder(depcache.ptr);
+
+	// The ordering treats packages without an archive filename as missing
+	// and skips their unpack constraints, so fill them in like DoInstall has.
+	// The acquire is never run, nothing is downloaded.
+	pkgAcquire acquire;
+	if (!recorder.GetArchives(&acquire, cache.unconst()->GetSourceList(), &records.records)) {
+		handle_errors();
+		throw std::runtime_error("Unable to find the archives of the marked changes.");
+	}
+
+	if (recorder.DoInstallPreFork() == pkgPackageManager::Failed) {
+		handle_errors();
+		throw std::runtime_error("Unable to order the marked changes.");
+	}
+
+	return std::make_unique<PkgActions>(PkgActions{std::move(recorder.actions)});
+}
+
 struct ProblemResolver {
 	pkgProblemResolver mutable resolver;
 
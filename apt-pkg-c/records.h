@@ -37,6 +37,17 @@ struct Parser {
 	/// Return the Source package version String.
 	String get_field(String field) const { return handle_string(ptr.RecordField(field.c_str())); }
 
+	/// Return the raw bytes of the field without assuming they are UTF-8.
+	Vec<u8> get_field_bytes(String field) const {
+		std::string value = ptr.RecordField(field.c_str());
+		if (value.empty()) { throw std::runtime_error("Field doesn't exist"); }
+
+		Vec<u8> bytes;
+		bytes.reserve(value.size());
+		for (char byte : value) { bytes.push_back(static_cast<u8>(byte)); }
+		return bytes;
+	}
+
 	// TODO: Lets Go Ahead and Bind HashStrings while we're here ffs
 	/// Find the hash of a Version. Returns Result if there is no hash.
 	String hash_find(String hash_type) const {
@@ -81,6 +92,10 @@ struct SourceRecords {
 	pkgSrcRecords mutable records;
 
 	void restart() const { records.Restart(); }
+
+	/// Step to the next source record.
+	UniquePtr<SourceParser> step() const { return std::make_unique<SourceParser>(records.Step()); }
+
 	UniquePtr<SourceParser> find(String name, bool src_only) const {
 		return std::make_unique<SourceParser>(records.Find(name.c_str(), src_only));
 	}
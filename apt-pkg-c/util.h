@@ -1,9 +1,16 @@
 #pragma once
 #include <apt-pkg/algorithms.h>
+#include <apt-pkg/aptconfiguration.h>
 #include <apt-pkg/cachefile.h>
+#include <apt-pkg/configuration.h>
+#include <apt-pkg/fileutl.h>
+#include <apt-pkg/hashes.h>
 #include <apt-pkg/install-progress.h>
 #include <apt-pkg/pkgsystem.h>
+#include <apt-pkg/strutl.h>
 #include <apt-pkg/version.h>
+#include <fcntl.h>
+#include <unistd.h>
 #include <cstdint>
 #include <sstream>
 #include "rust/cxx.h"
@@ -84,3 +91,63 @@ inline void apt_unlock_inner() {
 
 /// Check if the lockfile is locked.
 inline bool apt_is_locked() { return _system->IsLocked(); }
+
+/// Check if another process holds a lock on the dpkg database.
+///
+/// This only tests the locks with F_GETLK, it never takes them.
+inline bool dpkg_is_locked() {
+	std::string admin_dir = flNotFile(_config->FindFile("Dir::State::status"));
+
+	for (const char* name : {"lock-frontend", "lock"}) {
+		int fd = open(flCombine(admin_dir, name).c_str(), O_RDONLY);
+		if (fd < 0) { continue; }
+
+		struct flock fl = {};
+		fl.l_type = F_WRLCK;
+		fl.l_whence = SEEK_SET;
+
+		bool locked = fcntl(fd, F_GETLK, &fl) == 0 && fl.l_type != F_UNLCK;
+		close(fd);
+		if (locked) { return true; }
+	}
+	return false;
+}
+
+/// Convert a URI into the name apt uses for it in the lists directory.
+inline String uri_to_lists_filename(str uri) { return URItoFileName(std::string(uri)); }
+
+/// Read a file, decompressing it based on its extension.
+inline String read_compressed(str path) {
+	FileFd fd;
+	std::string content;
+
+	if (fd.Open(std::string(path), FileFd::ReadOnly, FileFd::Extension)) {
+		char buffer[4096];
+		unsigned long long actual = 0;
+		while (fd.Read(buffer, sizeof(buffer), &actual) && actual != 0) {
+			content.append(buffer, actual);
+		}
+		fd.Close();
+	}
+
+	handle_errors();
+	return content;
+}
+
+/// The file extensions of the compressors apt knows, in order of preference.
+///
+/// The empty extension of uncompressed files is included.
+inline Vec<String> compressor_extensions() {
+	Vec<String> extensions;
+	for (auto const& compressor : APT::Configuration::getCompressors()) {
+		extensions.push_back(compressor.Extension);
+	}
+	return extensions;
+}
+
+/// Return the md5 hash of a string as hex.
+inline String md5_sum(str data) {
+	Hashes hash(Hashes::MD5SUM);
+	hash.Add(reinterpret_cast<const unsigned char*>(data.data()), data.size());
+	return hash.GetHashString(Hashes::MD5SUM).HashValue();
+}
@@ -24,6 +24,48 @@
 //! md5sum hashing and file copying are provided to allow items to apply
 //! a number of transformations to the data files they are working with.
 
+/// The category of an item being acquired.
+///
+/// Front-ends can use this to group downloads,
+/// such as "Fetching package lists" and "Downloading packages".
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AcqItemType {
+	/// An `InRelease` or `Release` file.
+	Release = 0,
+	/// A detached `Release.gpg` signature.
+	Signature = 1,
+	/// A package index such as `Packages`, `Sources` or `Contents`.
+	Index = 2,
+	/// A `Translation-*` index.
+	Translation = 3,
+	/// A `.deb` archive.
+	Package = 4,
+	/// A changelog.
+	Changelog = 5,
+	/// Any other single file.
+	File = 6,
+}
+
+impl From<u8> for AcqItemType {
+	fn from(value: u8) -> Self {
+		match value {
+			0 => AcqItemType::Release,
+			1 => AcqItemType::Signature,
+			2 => AcqItemType::Index,
+			3 => AcqItemType::Translation,
+			4 => AcqItemType::Package,
+			5 => AcqItemType::Changelog,
+			6 => AcqItemType::File,
+			_ => panic!("AcqItemType is malformed?"),
+		}
+	}
+}
+
+impl raw::ItemDesc {
+	/// The category of this item.
+	pub fn item_type(&self) -> AcqItemType { AcqItemType::from(self.raw_item_type()) }
+}
+
 #[cxx::bridge]
 pub(crate) mod raw {
 	#[repr(u32)]
@@ -77,6 +119,11 @@ pub(crate) mod raw {
 		pub fn description(self: &ItemDesc) -> String;
 		/// Shorter description of this item.
 		pub fn short_desc(self: &ItemDesc) -> String;
+		/// The category of this item as a u8.
+		///
+		/// Use [`ItemDesc::item_type`] instead.
+		#[cxx_name = "item_type"]
+		fn raw_item_type(self: &ItemDesc) -> u8;
 		/// Underlying item which is to be downloaded.
 		pub fn owner(self: &ItemDesc) -> UniquePtr<Item>;
 
@@ -151,5 +198,14 @@ pub(crate) mod raw {
 		///
 		/// The returned UniquePtr cannot outlive the cache.
 		unsafe fn create_acquire() -> UniquePtr<PkgAcquire>;
+
+		/// Download `uri` into `dest_dir` and return the path of the file.
+		///
+		/// No hashes are checked, the caller must verify the contents.
+		pub fn download_file(
+			uri: &str,
+			dest_dir: &str,
+			progress: Pin<&mut AcqTextStatus>,
+		) -> Result<String>;
 	}
 }
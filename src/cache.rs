@@ -1,23 +1,30 @@
 //! Contains Cache related structs.
 
 use std::cell::OnceCell;
-use std::fs;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::ffi::OsStr;
 use std::path::Path;
+use std::time::Duration;
+use std::{fs, thread};
 
 use cxx::{Exception, UniquePtr};
 
-use crate::Package;
 use crate::config::{Config, init_config_system};
-use crate::depcache::DepCache;
+use crate::depcache::{DepCache, READONLY_ERROR};
 use crate::error::{AptErrors, pending_error};
-use crate::pkgmanager::raw::OrderResult;
+use crate::pkgmanager::raw::{OrderResult, ordered_actions};
 use crate::progress::{AcquireProgress, InstallProgress, OperationProgress};
 use crate::raw::{
 	IntoRawIter, IterPkgIterator, PackageManager, PkgCacheFile, PkgIterator, ProblemResolver,
 	create_cache, create_pkgmanager, create_problem_resolver,
 };
-use crate::records::{PackageRecords, SourceRecords};
-use crate::util::{apt_lock, apt_unlock, apt_unlock_inner};
+use crate::records::{PackageRecords, SourceRecord, SourceRecords};
+use crate::tagfile::{TagSection, parse_tagfile};
+use crate::util::{
+	DiskSpace, apt_is_locked, apt_lock, apt_unlock, apt_unlock_inner, dpkg_admin_dir,
+	dpkg_is_locked, list_file_paths, uri_to_lists_filename,
+};
+use crate::{DepType, Marked, Package, PkgCurrentState, Version};
 
 /// Selection of Upgrade type
 #[repr(i32)]
@@ -38,6 +45,37 @@ pub enum Upgrade {
 	SafeUpgrade = 3,
 }
 
+/// The `Priority` field of a package.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Priority {
+	/// Packages needed for the system to work at all.
+	Required,
+	/// Packages expected on any Unix-like system.
+	Important,
+	/// Packages that make up a reasonably small but not too limited system.
+	Standard,
+	/// The default priority for most packages.
+	Optional,
+	/// Deprecated, now treated the same as `Optional`.
+	Extra,
+}
+
+impl Priority {
+	/// Parse a priority string as shown in `apt show`.
+	///
+	/// Returns [`None`] if it isn't a known priority.
+	pub fn from_name(name: &str) -> Option<Priority> {
+		Some(match name.to_ascii_lowercase().as_str() {
+			"required" => Priority::Required,
+			"important" => Priority::Important,
+			"standard" => Priority::Standard,
+			"optional" => Priority::Optional,
+			"extra" => Priority::Extra,
+			_ => return None,
+		})
+	}
+}
+
 /// Selection of how to sort
 enum Sort {
 	/// Disable the sort method.
@@ -56,6 +94,7 @@ pub struct PackageSort {
 	installed: Sort,
 	auto_installed: Sort,
 	auto_removable: Sort,
+	priority: Option<Priority>,
 }
 
 impl Default for PackageSort {
@@ -67,6 +106,7 @@ impl Default for PackageSort {
 			installed: Sort::Disable,
 			auto_installed: Sort::Disable,
 			auto_removable: Sort::Disable,
+			priority: None,
 		}
 	}
 }
@@ -138,6 +178,259 @@ impl PackageSort {
 		self.auto_removable = Sort::Reverse;
 		self
 	}
+
+	/// Only packages whose candidate has this priority will be included.
+	pub fn priority(mut self, priority: Priority) -> Self {
+		self.priority = Some(priority);
+		self
+	}
+}
+
+/// The state of the system as reported by [`Cache::check_consistency`].
+#[derive(Debug)]
+pub struct ConsistencyReport<'a> {
+	/// The number of packages with broken dependencies.
+	pub broken_count: u32,
+	/// Packages dpkg did not finish with.
+	///
+	/// This includes any package that is unpacked, half-installed,
+	/// half-configured or waiting on triggers.
+	pub half_configured: Vec<Package<'a>>,
+	/// `true` if the dpkg database is locked.
+	pub locked: bool,
+	/// `true` if a previous dpkg run was interrupted
+	/// and `dpkg --configure -a` needs to be run.
+	pub interrupted: bool,
+}
+
+impl ConsistencyReport<'_> {
+	/// Returns `true` if nothing is stopping a new transaction.
+	pub fn is_ok(&self) -> bool {
+		self.broken_count == 0
+			&& self.half_configured.is_empty()
+			&& !self.locked
+			&& !self.interrupted
+	}
+}
+
+/// The header of the lockfile written by [`Cache::export_lockfile`].
+const LOCKFILE_HEADER: &str = "# rust-apt lockfile v1";
+
+/// The entries of a manifest that [`Cache::apply_manifest`] couldn't mark.
+///
+/// Names are `name:arch` when the stanza has an `Architecture`.
+#[derive(Debug, Default)]
+pub struct ManifestReport {
+	/// Packages that aren't in the cache.
+	pub missing: Vec<String>,
+	/// Packages whose requested version isn't available,
+	/// paired with that version.
+	pub missing_versions: Vec<(String, String)>,
+}
+
+/// A difference between a lockfile and the installed system.
+///
+/// Returned by [`Cache::verify_lockfile`]. Names are `name:arch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LockDiff {
+	/// The package is in the lockfile but it's not installed.
+	Missing { name: String, version: String },
+	/// The package is installed but it's not in the lockfile.
+	Extra { name: String, version: String },
+	/// The package is installed at a different version than locked.
+	Version {
+		name: String,
+		locked: String,
+		installed: String,
+	},
+	/// The automatically installed flag is different than locked.
+	Auto {
+		name: String,
+		locked: bool,
+		installed: bool,
+	},
+	/// A line in the lockfile that could not be parsed.
+	Malformed(String),
+}
+
+/// The marked changes of a [`Cache`] grouped the way apt shows them.
+///
+/// Returned by [`Cache::changes_summary`]. Packages are sorted by name.
+#[derive(Debug)]
+pub struct ChangesSummary<'a> {
+	/// Packages that will be newly installed.
+	pub install: Vec<Package<'a>>,
+	/// Packages that will be upgraded.
+	pub upgrade: Vec<Package<'a>>,
+	/// Packages that will be downgraded.
+	pub downgrade: Vec<Package<'a>>,
+	/// Packages that will be reinstalled.
+	pub reinstall: Vec<Package<'a>>,
+	/// Packages that will be removed or purged.
+	pub remove: Vec<Package<'a>>,
+	/// The number of bytes that need to be downloaded.
+	pub download_size: u64,
+	/// The disk space that will be used or freed.
+	pub disk_space: DiskSpace,
+}
+
+/// How many times to try taking the apt lock before giving up.
+///
+/// Only lock contention is retried, while another process holds the dpkg lock.
+/// Any other error, such as permission denied, is returned at once.
+#[derive(Debug, Clone)]
+pub struct LockRetry {
+	/// The number of retries after the first attempt fails.
+	pub attempts: u32,
+	/// How long to wait between attempts.
+	pub delay: Duration,
+}
+
+/// Options for [`Cache::commit_with_options`].
+#[derive(Debug, Clone, Default)]
+pub struct CommitOptions {
+	/// Retry taking the apt lock if another process holds it,
+	/// like `-o DPkg::Lock::Timeout`.
+	///
+	/// If [`None`], commit fails as soon as the lock can't be taken.
+	pub lock_retry: Option<LockRetry>,
+}
+
+/// The saved state of a single package in a [`MarkSnapshot`].
+#[derive(Debug, Clone)]
+struct MarkState {
+	marked: Marked,
+	// The install version, or the candidate if the package is kept.
+	version: Option<String>,
+	auto: bool,
+}
+
+/// The marked changes of a [`Cache`] saved by [`Cache::snapshot`].
+///
+/// Pass it to [`Cache::restore`] to put the marks back
+/// after trying out a different set of changes.
+#[derive(Debug, Clone, Default)]
+pub struct MarkSnapshot {
+	// Keyed by `name:arch` so it can be looked up again.
+	packages: BTreeMap<String, MarkState>,
+}
+
+/// The fields of a repository's `Release` or `InRelease` file.
+///
+/// Returned by [`Cache::release_info`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseInfo {
+	/// Who publishes the repository, e.g. `Debian`.
+	pub origin: Option<String>,
+	/// A label for the repository, often the same as the origin.
+	pub label: Option<String>,
+	/// The release class, e.g. `stable` or `unstable`.
+	pub suite: Option<String>,
+	/// The name of the release, e.g. `bookworm`.
+	pub codename: Option<String>,
+	/// When the Release file was generated, in RFC 2822 format.
+	pub date: Option<String>,
+	/// When the Release file should no longer be trusted, in RFC 2822 format.
+	pub valid_until: Option<String>,
+	/// The architectures the repository has packages for.
+	pub architectures: Vec<String>,
+	/// The components of the repository, e.g. `main` and `contrib`.
+	pub components: Vec<String>,
+}
+
+impl From<&TagSection> for ReleaseInfo {
+	fn from(section: &TagSection) -> Self {
+		let field = |key: &str| section.get(key).cloned();
+		let list = |key: &str| {
+			section
+				.get(key)
+				.map(|value| value.split_whitespace().map(str::to_string).collect())
+				.unwrap_or_default()
+		};
+
+		ReleaseInfo {
+			origin: field("Origin"),
+			label: field("Label"),
+			suite: field("Suite"),
+			codename: field("Codename"),
+			date: field("Date"),
+			valid_until: field("Valid-Until"),
+			architectures: list("Architectures"),
+			components: list("Components"),
+		}
+	}
+}
+
+/// The difference between a [`MarkSnapshot`] and the current marks.
+///
+/// Returned by [`Cache::plan_diff`].
+#[derive(Debug)]
+pub struct PlanDiff<'a> {
+	/// Packages that are newly marked, or marked differently than before.
+	pub marked: Vec<(Package<'a>, Marked)>,
+	/// Packages that were marked before and aren't anymore.
+	pub unmarked: Vec<Package<'a>>,
+}
+
+impl PlanDiff<'_> {
+	/// Returns `true` if nothing changed.
+	pub fn is_empty(&self) -> bool { self.marked.is_empty() && self.unmarked.is_empty() }
+}
+
+/// An installed package that has a newer candidate.
+///
+/// Returned by [`Cache::available_upgrades`].
+#[derive(Debug)]
+pub struct UpgradeInfo<'a> {
+	/// The upgradable package.
+	pub package: Package<'a>,
+	/// The version that is installed now.
+	pub installed: Version<'a>,
+	/// The version that would be installed by an upgrade.
+	pub candidate: Version<'a>,
+	/// The size of the candidate's .deb.
+	pub download_size: u64,
+}
+
+/// What dpkg will do with a package in a [`PlanStep`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+	/// Unpack the .deb.
+	Unpack,
+	/// Configure an unpacked package.
+	Configure,
+	/// Remove the package, leaving its config files.
+	Remove,
+	/// Remove the package along with its config files.
+	Purge,
+}
+
+impl From<u8> for Op {
+	fn from(value: u8) -> Self {
+		match value {
+			0 => Op::Unpack,
+			1 => Op::Configure,
+			2 => Op::Remove,
+			3 => Op::Purge,
+			_ => panic!("Op is malformed?"),
+		}
+	}
+}
+
+/// A single step of [`Cache::install_plan`].
+#[derive(Debug)]
+pub struct PlanStep<'a> {
+	/// The package the step is for.
+	pub package: Package<'a>,
+	/// The version being unpacked or configured,
+	/// or the installed version for removals.
+	pub version: Version<'a>,
+	/// What dpkg will do.
+	pub op: Op,
+	/// The size of the .deb, only set for [`Op::Unpack`].
+	pub download_size: u64,
+	/// The installed size of `version`.
+	pub installed_size: u64,
 }
 
 /// The main struct for accessing any and all `apt` data.
@@ -149,6 +442,7 @@ pub struct Cache {
 	pkgmanager: OnceCell<UniquePtr<PackageManager>>,
 	problem_resolver: OnceCell<UniquePtr<ProblemResolver>>,
 	local_debs: Vec<String>,
+	readonly: bool,
 }
 
 impl Cache {
@@ -185,9 +479,71 @@ impl Cache {
 				.filter(|f| f.ends_with(".deb"))
 				.map(|f| f.to_string())
 				.collect(),
+			readonly: false,
 		})
 	}
 
+	/// Same as [`Cache::new`], but the cache can only be queried.
+	///
+	/// Anything that would change the marks returns an [`AptErrors`] saying
+	/// "The cache was opened read-only" without changing anything.
+	/// That covers every `mark_*` method of [`Package`],
+	/// [`Version::set_candidate`], [`Cache::fix_broken`],
+	/// [`Cache::upgrade`], [`Cache::resolve`], [`Cache::restore`] and
+	/// [`Cache::apply_manifest`], along with [`DepCache::clear_marked`],
+	/// [`DepCache::init`], [`DepCache::upgrade`] and
+	/// [`DepCache::action_group`].
+	///
+	/// [`Cache::update`], [`Cache::do_install`] and [`Cache::commit`]
+	/// return an [`AptErrors`] as well.
+	pub fn new_readonly<T: AsRef<str>>(local_files: &[T]) -> Result<Cache, AptErrors> {
+		let mut cache = Self::new(local_files)?;
+		cache.readonly = true;
+		Ok(cache)
+	}
+
+	/// Return an error if the cache is read-only.
+	fn check_readonly(&self) -> Result<(), AptErrors> {
+		if self.readonly {
+			return Err(AptErrors::from(READONLY_ERROR.to_string()));
+		}
+		Ok(())
+	}
+
+	/// Same as [`Cache::new`], but set the configuration `overrides` first.
+	///
+	/// The overrides are applied after the configuration files are read
+	/// and before the system and the cache are initialized,
+	/// so they take effect for everything the cache loads.
+	///
+	/// The overrides stay set for the rest of the session.
+	///
+	/// # Example:
+	/// ```
+	/// use std::collections::HashMap;
+	///
+	/// use rust_apt::cache::Cache;
+	///
+	/// let overrides = HashMap::from([(
+	///     "APT::Install-Recommends".to_string(),
+	///     "false".to_string(),
+	/// )]);
+	/// let cache = Cache::new_with_config::<&str>(overrides, &[]).unwrap();
+	/// ```
+	pub fn new_with_config<T: AsRef<str>>(
+		overrides: HashMap<String, String>,
+		local_files: &[T],
+	) -> Result<Cache, AptErrors> {
+		// Init the config first or the overrides would be lost to the defaults.
+		let config = Config::new();
+		for (key, value) in &overrides {
+			config.set(key, value);
+		}
+
+		// Cache::new reinitializes the system with the overrides in place.
+		Self::new(local_files)
+	}
+
 	/// Internal Method for generating the package list.
 	pub fn raw_pkgs(&self) -> impl Iterator<Item = UniquePtr<PkgIterator>> {
 		unsafe { self.begin().raw_iter() }
@@ -195,8 +551,11 @@ impl Cache {
 
 	/// Get the DepCache
 	pub fn depcache(&self) -> &DepCache {
-		self.depcache
-			.get_or_init(|| DepCache::new(unsafe { self.create_depcache() }))
+		self.depcache.get_or_init(|| {
+			let mut depcache = DepCache::new(unsafe { self.create_depcache() });
+			depcache.readonly = self.readonly;
+			depcache
+		})
 	}
 
 	/// Get the PkgRecords
@@ -340,6 +699,18 @@ impl Cache {
 				},
 			}
 
+			if let Some(priority) = sort.priority {
+				// Packages without a candidate have no priority to match.
+				let Some(cand) = (unsafe { self.depcache().candidate_version(&pkg).make_safe() })
+				else {
+					continue;
+				};
+
+				if cand.priority_str().ok().and_then(Priority::from_name) != Some(priority) {
+					continue;
+				}
+			}
+
 			// If this is reached we're clear to include the package.
 			pkg_list.push(pkg);
 		}
@@ -376,6 +747,7 @@ impl Cache {
 	///   Permission denied)
 	/// * E:Unable to lock directory /var/lib/apt/lists/
 	pub fn update(self, progress: &mut AcquireProgress) -> Result<(), AptErrors> {
+		self.check_readonly()?;
 		Ok(self.ptr.update(progress.mut_status())?)
 	}
 
@@ -393,9 +765,8 @@ impl Cache {
 	/// ```
 	pub fn upgrade(&self, upgrade_type: Upgrade) -> Result<(), AptErrors> {
 		let mut progress = OperationProgress::quiet();
-		Ok(self
-			.depcache()
-			.upgrade(progress.pin().as_mut(), upgrade_type as i32)?)
+		self.depcache()
+			.upgrade(progress.pin().as_mut(), upgrade_type as i32)
 	}
 
 	/// Resolve dependencies with the changes marked on all packages. This marks
@@ -414,6 +785,7 @@ impl Cache {
 	/// Returns [`Err`] if there was an error reaching dependency resolution.
 	#[allow(clippy::result_unit_err)]
 	pub fn resolve(&self, fix_broken: bool) -> Result<(), AptErrors> {
+		self.check_readonly()?;
 		Ok(self
 			.resolver()
 			.resolve(fix_broken, OperationProgress::quiet().pin().as_mut())?)
@@ -429,13 +801,13 @@ impl Cache {
 	///
 	/// let cache = new_cache!().unwrap();
 	///
-	/// cache.fix_broken();
+	/// cache.fix_broken().unwrap();
 	///
 	/// for pkg in cache.get_changes(false) {
 	///     println!("Pkg Name: {}", pkg.name())
 	/// }
 	/// ```
-	pub fn fix_broken(&self) -> bool { self.depcache().fix_broken() }
+	pub fn fix_broken(&self) -> Result<bool, AptErrors> { self.depcache().fix_broken() }
 
 	/// Fetch any archives needed to complete the transaction.
 	///
@@ -452,7 +824,7 @@ impl Cache {
 	/// let pkg = cache.get("neovim").unwrap();
 	/// let mut progress = AcquireProgress::apt();
 	///
-	/// pkg.mark_install(true, true);
+	/// pkg.mark_install(true, true).unwrap();
 	/// pkg.protect();
 	/// cache.resolve(true).unwrap();
 	///
@@ -502,7 +874,7 @@ impl Cache {
 	/// let mut acquire_progress = AcquireProgress::apt();
 	/// let mut install_progress = InstallProgress::apt();
 	///
-	/// pkg.mark_install(true, true);
+	/// pkg.mark_install(true, true).unwrap();
 	/// pkg.protect();
 	/// cache.resolve(true).unwrap();
 	///
@@ -527,6 +899,7 @@ impl Cache {
 	/// * W:Problem unlinking the file /var/cache/apt/pkgcache.bin -
 	///   pkgDPkgPM::Go (13: Permission denied)
 	pub fn do_install(self, progress: &mut InstallProgress) -> Result<(), AptErrors> {
+		self.check_readonly()?;
 		let res = match progress {
 			InstallProgress::Fancy(inner) => self.pkg_manager().do_install(inner.pin().as_mut()),
 			InstallProgress::Fd(fd) => self.pkg_manager().do_install_fd(*fd),
@@ -565,7 +938,7 @@ impl Cache {
 	/// let mut acquire_progress = AcquireProgress::apt();
 	/// let mut install_progress = InstallProgress::apt();
 	///
-	/// pkg.mark_install(true, true);
+	/// pkg.mark_install(true, true).unwrap();
 	/// pkg.protect();
 	/// cache.resolve(true).unwrap();
 	///
@@ -577,8 +950,37 @@ impl Cache {
 		progress: &mut AcquireProgress,
 		install_progress: &mut InstallProgress,
 	) -> Result<(), AptErrors> {
+		self.commit_with_options(progress, install_progress, &CommitOptions::default())
+	}
+
+	/// Same as [`Cache::commit`], but with [`CommitOptions`].
+	///
+	/// Taking the lock is only retried while another process holds the dpkg
+	/// lock. If it is still held after every retry, the error from the last
+	/// attempt is returned. Other lock errors are returned without retrying.
+	pub fn commit_with_options(
+		self,
+		progress: &mut AcquireProgress,
+		install_progress: &mut InstallProgress,
+		options: &CommitOptions,
+	) -> Result<(), AptErrors> {
+		self.check_readonly()?;
+
 		// Lock the whole thing so as to prevent tamper
-		apt_lock()?;
+		match &options.lock_retry {
+			Some(retry) => {
+				let mut attempt = 0;
+				while let Err(err) = apt_lock() {
+					// Waiting won't fix anything but another process holding the lock.
+					if attempt >= retry.attempts || !dpkg_is_locked() {
+						return Err(err);
+					}
+					attempt += 1;
+					thread::sleep(retry.delay);
+				}
+			},
+			None => apt_lock()?,
+		}
 
 		let config = Config::new();
 		let archive_dir = config.dir("Dir::Cache::Archives", "/var/cache/apt/archives/");
@@ -649,6 +1051,729 @@ impl Cache {
 			.into_iter()
 			.map(|pkg_ptr| Package::new(self, pkg_ptr))
 	}
+
+	/// Check if the system is in a state where it is safe to operate on.
+	///
+	/// # Example:
+	/// ```
+	/// use rust_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let report = cache.check_consistency();
+	/// if !report.is_ok() {
+	///     println!("{report:#?}");
+	/// }
+	/// ```
+	pub fn check_consistency(&self) -> ConsistencyReport<'_> {
+		let half_configured = self
+			.iter()
+			.filter(|pkg| {
+				matches!(
+					pkg.current_state(),
+					PkgCurrentState::UnPacked
+						| PkgCurrentState::HalfConfigured
+						| PkgCurrentState::HalfInstalled
+						| PkgCurrentState::TriggersAwaited
+						| PkgCurrentState::TriggersPending
+				)
+			})
+			.collect();
+
+		// dpkg keeps a journal in the updates directory
+		// of every status change it hasn't written out yet.
+		// Any file named with only digits means a run was interrupted.
+		let interrupted = fs::read_dir(dpkg_admin_dir().join("updates")).is_ok_and(|entries| {
+			entries.flatten().any(|entry| {
+				let name = entry.file_name();
+				let name = name.to_string_lossy();
+				!name.is_empty() && name.bytes().all(|c| c.is_ascii_digit())
+			})
+		});
+
+		ConsistencyReport {
+			broken_count: self.depcache().broken_count(),
+			half_configured,
+			locked: apt_is_locked() || dpkg_is_locked(),
+			interrupted,
+		}
+	}
+
+	/// Every installed package as `name:arch` with its version and auto flag.
+	fn lock_entries(&self) -> BTreeMap<String, (String, bool)> {
+		self.packages(&PackageSort::default().installed())
+			.filter_map(|pkg| {
+				let version = pkg.installed()?.version().to_string();
+				Some((pkg.fullname(false), (version, pkg.is_auto_installed())))
+			})
+			.collect()
+	}
+
+	/// Export every installed package as a lockfile.
+	///
+	/// The format is stable, one package per line sorted by name:
+	///
+	/// ```text
+	/// # rust-apt lockfile v1
+	/// apt:amd64=2.6.1 manual
+	/// libapt-pkg6.0:amd64=2.6.1 auto
+	/// ```
+	///
+	/// Empty lines and lines starting with `#` are ignored
+	/// by [`Cache::verify_lockfile`].
+	pub fn export_lockfile(&self) -> String {
+		let mut lockfile = format!("{LOCKFILE_HEADER}\n");
+		for (name, (version, auto)) in self.lock_entries() {
+			let flag = if auto { "auto" } else { "manual" };
+			lockfile += &format!("{name}={version} {flag}\n");
+		}
+		lockfile
+	}
+
+	/// Export every installed package as a deb822 manifest,
+	/// one stanza per package sorted by name:
+	///
+	/// ```text
+	/// Package: apt
+	/// Version: 2.6.1
+	/// Architecture: amd64
+	/// Auto-Installed: no
+	/// Status: install ok installed
+	/// ```
+	///
+	/// The Status field uses the same words as the dpkg status file.
+	/// The result can be read back with [`crate::tagfile::parse_tagfile`].
+	pub fn export_manifest(&self) -> String {
+		let mut pkgs: Vec<_> = self.packages(&PackageSort::default().installed()).collect();
+		pkgs.sort_by_cached_key(|pkg| pkg.fullname(false));
+
+		let mut manifest = String::new();
+		for pkg in pkgs {
+			let Some(version) = pkg.installed() else {
+				continue;
+			};
+
+			if !manifest.is_empty() {
+				manifest.push('\n');
+			}
+
+			let auto = if pkg.is_auto_installed() { "yes" } else { "no" };
+			manifest += &format!("Package: {}\n", pkg.name());
+			manifest += &format!("Version: {}\n", version.version());
+			manifest += &format!("Architecture: {}\n", pkg.arch());
+			manifest += &format!("Auto-Installed: {auto}\n");
+			manifest += &format!(
+				"Status: {} {} {}\n",
+				pkg.selected_state(),
+				pkg.inst_state(),
+				pkg.current_state()
+			);
+		}
+		manifest
+	}
+
+	/// Mark the packages of a manifest from [`Cache::export_manifest`]
+	/// for installation.
+	///
+	/// Each package is marked at the version in its stanza
+	/// and its auto installed flag is set from `Auto-Installed`.
+	/// Packages or versions that aren't available are skipped
+	/// and listed in the [`ManifestReport`].
+	///
+	/// Returns [`Err`] if the cache was opened read-only.
+	pub fn apply_manifest(&self, content: &str) -> Result<ManifestReport, AptErrors> {
+		self.check_readonly()?;
+		let mut report = ManifestReport::default();
+
+		for section in parse_tagfile(content)? {
+			let Some(name) = section.get("Package") else {
+				continue;
+			};
+			let name = match section.get("Architecture") {
+				Some(arch) => format!("{name}:{arch}"),
+				None => name.to_string(),
+			};
+
+			let Some(pkg) = self.get(&name) else {
+				report.missing.push(name);
+				continue;
+			};
+
+			if let Some(version) = section.get("Version") {
+				let Some(ver) = pkg.get_version(version) else {
+					report.missing_versions.push((name, version.to_string()));
+					continue;
+				};
+				ver.set_candidate()?;
+			}
+
+			let auto = section
+				.get("Auto-Installed")
+				.is_some_and(|auto| auto == "yes");
+			pkg.mark_install(true, !auto)?;
+			pkg.mark_auto(auto)?;
+		}
+		Ok(report)
+	}
+
+	/// Compare a lockfile from [`Cache::export_lockfile`]
+	/// against the installed system.
+	///
+	/// An empty Vec means the system matches the lockfile.
+	pub fn verify_lockfile(&self, content: &str) -> Vec<LockDiff> {
+		let mut diffs = vec![];
+		let mut locked = BTreeMap::new();
+
+		for line in content.lines().map(str::trim) {
+			if line.is_empty() || line.starts_with('#') {
+				continue;
+			}
+
+			let entry = line.rsplit_once(' ').and_then(|(pkg, flag)| {
+				let (name, version) = pkg.split_once('=')?;
+				let auto = match flag {
+					"auto" => true,
+					"manual" => false,
+					_ => return None,
+				};
+				Some((name.to_string(), (version.to_string(), auto)))
+			});
+
+			match entry {
+				Some((name, value)) => {
+					locked.insert(name, value);
+				},
+				None => diffs.push(LockDiff::Malformed(line.to_string())),
+			}
+		}
+
+		let mut installed = self.lock_entries();
+		for (name, (version, auto)) in locked {
+			let Some((inst_version, inst_auto)) = installed.remove(&name) else {
+				diffs.push(LockDiff::Missing { name, version });
+				continue;
+			};
+
+			if version != inst_version {
+				diffs.push(LockDiff::Version {
+					name: name.clone(),
+					locked: version,
+					installed: inst_version,
+				});
+			}
+
+			if auto != inst_auto {
+				diffs.push(LockDiff::Auto {
+					name,
+					locked: auto,
+					installed: inst_auto,
+				});
+			}
+		}
+
+		// Whatever is left over isn't in the lockfile.
+		for (name, (version, _)) in installed {
+			diffs.push(LockDiff::Extra { name, version });
+		}
+		diffs
+	}
+
+	/// Find the installed package that owns `path`, like `dpkg -S`.
+	///
+	/// Only the dpkg `.list` files of installed packages are searched,
+	/// so diversions and files created by maintainer scripts are not known.
+	/// If more than one package owns the path, such as with directories,
+	/// the first one by name is returned.
+	///
+	/// On a merged `/usr` system `/bin/sh` and `/usr/bin/sh` are the same
+	/// file, so a path is also looked up with and without the `/usr` prefix.
+	pub fn owner_of_file(&self, path: &Path) -> Option<Package<'_>> {
+		let mut paths = vec![path.to_path_buf()];
+		if let Ok(real) = fs::canonicalize(path) {
+			paths.push(real);
+		}
+		for path in paths.clone() {
+			match path.strip_prefix("/usr") {
+				Ok(rest) => paths.push(Path::new("/").join(rest)),
+				Err(_) => paths.push(Path::new("/usr").join(path.strip_prefix("/").ok()?)),
+			}
+		}
+
+		let mut owners = vec![];
+		for entry in fs::read_dir(dpkg_admin_dir().join("info")).ok()?.flatten() {
+			let file = entry.path();
+			if file.extension() != Some(OsStr::new("list")) {
+				continue;
+			}
+
+			let Ok(list) = fs::read(&file) else {
+				continue;
+			};
+			if !list_file_paths(&list)
+				.iter()
+				.any(|file| paths.contains(file))
+			{
+				continue;
+			}
+
+			// The list is named after the package, with `:arch` for Multi-Arch: same.
+			let name = file.file_stem()?.to_string_lossy();
+			if let Some(pkg) = self.get(&name).filter(|pkg| pkg.is_installed()) {
+				owners.push(pkg);
+			}
+		}
+
+		owners.into_iter().min_by_key(|pkg| pkg.fullname(false))
+	}
+
+	/// Returns every virtual package along with the packages that provide it,
+	/// sorted by name.
+	///
+	/// Virtual packages that nothing provides are left out.
+	pub fn virtual_packages(&self) -> Vec<(Package<'_>, Vec<Package<'_>>)> {
+		self.packages(&PackageSort::default().only_virtual().names())
+			.map(|pkg| {
+				let providers = pkg.providing_packages();
+				(pkg, providers)
+			})
+			.filter(|(_, providers)| !providers.is_empty())
+			.collect()
+	}
+
+	/// Returns the installed packages whose installed version
+	/// can't be downloaded from any source.
+	///
+	/// These are the packages apt shows as `[installed,local]`,
+	/// usually ones that were installed from a `.deb` file.
+	pub fn locally_installed(&self) -> Vec<Package<'_>> {
+		self.packages(&PackageSort::default().installed().names())
+			.filter(|pkg| pkg.installed().is_some_and(|ver| !ver.is_downloadable()))
+			.collect()
+	}
+
+	/// Returns every installed package that can be upgraded,
+	/// along with its installed and candidate versions.
+	pub fn available_upgrades(&self) -> Vec<UpgradeInfo<'_>> {
+		self.packages(&PackageSort::default().upgradable())
+			.filter_map(|package| {
+				let installed = package.installed()?;
+				let candidate = package.candidate()?;
+				Some(UpgradeInfo {
+					download_size: candidate.size(),
+					package,
+					installed,
+					candidate,
+				})
+			})
+			.collect()
+	}
+
+	/// Returns the distinct names of every source package in the source
+	/// records, sorted a -> z.
+	///
+	/// This will be empty if there are no `deb-src` entries.
+	pub fn source_packages(&self) -> Vec<String> {
+		let Ok(src_records) = self.source_records() else {
+			return vec![];
+		};
+
+		// Start from the top in case a previous lookup left the records elsewhere.
+		src_records.restart();
+
+		let mut names = BTreeSet::new();
+		while let Some(record) = src_records.step() {
+			names.insert(record.package());
+		}
+		names.into_iter().collect()
+	}
+
+	/// Get an owned copy of the source record for the source package `name`.
+	pub fn get_source_record(&self, name: &str) -> Option<SourceRecord> {
+		let src_records = self.source_records().ok()?;
+		src_records.restart();
+
+		let record = SourceRecord::from(&**src_records.lookup(name.to_string(), true)?);
+		src_records.restart();
+		Some(record)
+	}
+
+	/// Group the marked changes the way apt shows them
+	/// before asking to continue.
+	pub fn changes_summary(&self) -> ChangesSummary<'_> {
+		let mut summary = ChangesSummary {
+			install: vec![],
+			upgrade: vec![],
+			downgrade: vec![],
+			reinstall: vec![],
+			remove: vec![],
+			download_size: self.depcache().download_size(),
+			disk_space: self.depcache().disk_size(),
+		};
+
+		for pkg in self.get_changes(true) {
+			match pkg.marked() {
+				Marked::NewInstall | Marked::Install => summary.install.push(pkg),
+				Marked::Upgrade => summary.upgrade.push(pkg),
+				Marked::Downgrade => summary.downgrade.push(pkg),
+				Marked::ReInstall => summary.reinstall.push(pkg),
+				Marked::Remove | Marked::Purge => summary.remove.push(pkg),
+				_ => {},
+			}
+		}
+		summary
+	}
+
+	/// Mark the changes of `apt dist-upgrade` and return a summary of them.
+	///
+	/// ## allow_removals:
+	///   * [true] = Packages may be removed to complete the upgrade.
+	///   * [false] = If any packages would be removed, the marks are put back
+	///     the way they were and an [`AptErrors`] naming them is returned.
+	pub fn dist_upgrade(&self, allow_removals: bool) -> Result<ChangesSummary<'_>, AptErrors> {
+		let snapshot = self.snapshot();
+
+		if let Err(err) = self.upgrade(Upgrade::FullUpgrade) {
+			self.restore(&snapshot)?;
+			return Err(err);
+		}
+
+		let summary = self.changes_summary();
+		if allow_removals || summary.remove.is_empty() {
+			return Ok(summary);
+		}
+
+		let names: Vec<String> = summary
+			.remove
+			.iter()
+			.map(|pkg| pkg.fullname(true))
+			.collect();
+		self.restore(&snapshot)?;
+
+		Err(AptErrors::from(format!(
+			"The following packages would be removed: {}",
+			names.join(" ")
+		)))
+	}
+
+	/// Estimate how long the marked changes will take to download and install.
+	///
+	/// `recent_cps` is the download speed in bytes per second,
+	/// such as `AcqTextStatus::current_cps()` from the acquire progress.
+	/// If it is `0` only the install time is estimated.
+	///
+	/// The install time is the sum of [`crate::Version::install_weight`]
+	/// for every changed package, so it is only a rough guess.
+	/// It can be tuned with `rust_apt::InstallWeight::Package`
+	/// and `rust_apt::InstallWeight::MiB`.
+	pub fn estimated_transaction_time(&self, recent_cps: u64) -> Duration {
+		let config = Config::new();
+		let mut estimate = Duration::ZERO;
+		if recent_cps != 0 {
+			estimate +=
+				Duration::from_secs_f64(self.depcache().download_size() as f64 / recent_cps as f64);
+		}
+
+		for pkg in self.get_changes(false) {
+			// Removals don't have an install version.
+			if let Some(ver) = pkg.install_version().or_else(|| pkg.installed()) {
+				estimate += ver.install_weight(&config);
+			}
+		}
+		estimate
+	}
+
+	/// Save the currently marked changes so they can be put back
+	/// with [`Cache::restore`].
+	///
+	/// The marks, install versions and automatically installed flags are
+	/// saved, along with the candidates of kept and held packages.
+	/// Candidates of packages that aren't installed or marked are not.
+	pub fn snapshot(&self) -> MarkSnapshot {
+		let packages = self
+			.iter()
+			.filter(|pkg| pkg.is_installed() || pkg.marked_install() || pkg.marked_delete())
+			.map(|pkg| {
+				let marked = pkg.marked();
+				let version = match marked {
+					Marked::Keep | Marked::Held => pkg.candidate(),
+					_ => pkg.install_version(),
+				};
+				let state = MarkState {
+					marked,
+					version: version.map(|ver| ver.version().to_string()),
+					auto: pkg.is_auto_installed(),
+				};
+				(pkg.fullname(false), state)
+			})
+			.collect();
+
+		MarkSnapshot { packages }
+	}
+
+	/// Clear the marked changes and replay a [`MarkSnapshot`].
+	///
+	/// Returns [`Err`] if the cache was opened read-only.
+	pub fn restore(&self, snapshot: &MarkSnapshot) -> Result<(), AptErrors> {
+		self.depcache().clear_marked()?;
+
+		let pkgs: Vec<(Package, &MarkState)> = snapshot
+			.packages
+			.iter()
+			.filter_map(|(name, state)| Some((self.get(name)?, state)))
+			.collect();
+
+		// Removals go first so they can't be undone by installing dependencies.
+		for (pkg, state) in &pkgs {
+			match state.marked {
+				Marked::Remove => pkg.mark_delete(false)?,
+				Marked::Purge => pkg.mark_delete(true)?,
+				_ => continue,
+			};
+		}
+
+		for (pkg, state) in &pkgs {
+			match state.marked {
+				Marked::NewInstall | Marked::Install | Marked::Upgrade | Marked::Downgrade => {
+					if let Some(ver) = state.version.as_deref().and_then(|v| pkg.get_version(v)) {
+						ver.set_candidate()?;
+					}
+					pkg.mark_install(false, false)?;
+				},
+				Marked::ReInstall => {
+					pkg.mark_reinstall(true)?;
+				},
+				Marked::Keep | Marked::Held => {
+					// Clearing the marks resets the candidate, which decides
+					// if a kept package is held back from an upgrade.
+					let candidate = pkg.candidate().map(|ver| ver.version().to_string());
+					let changed = state
+						.version
+						.as_deref()
+						.filter(|_| state.version != candidate);
+					if let Some(ver) = changed.and_then(|v| pkg.get_version(v)) {
+						ver.set_candidate()?;
+					}
+					if pkg.marked() != state.marked {
+						pkg.mark_keep()?;
+					}
+				},
+				_ => {},
+			}
+		}
+
+		for (pkg, state) in &pkgs {
+			if pkg.is_auto_installed() != state.auto {
+				pkg.mark_auto(state.auto)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Compare the current marks against a [`MarkSnapshot`] taken earlier.
+	pub fn plan_diff(&self, before: &MarkSnapshot) -> PlanDiff<'_> {
+		let is_change =
+			|marked: Marked| !matches!(marked, Marked::Keep | Marked::Held | Marked::None);
+
+		let mut marked = vec![];
+		for pkg in self.get_changes(true) {
+			let now = pkg.marked();
+			let was = before
+				.packages
+				.get(&pkg.fullname(false))
+				.map(|state| state.marked);
+			if is_change(now) && was != Some(now) {
+				marked.push((pkg, now));
+			}
+		}
+
+		let unmarked = before
+			.packages
+			.iter()
+			.filter(|(_, state)| is_change(state.marked))
+			.filter_map(|(name, _)| self.get(name))
+			.filter(|pkg| !is_change(pkg.marked()))
+			.collect();
+
+		PlanDiff { marked, unmarked }
+	}
+
+	/// Returns the marked changes in the order dpkg will process them.
+	///
+	/// dpkg is not run and nothing is downloaded, this only asks the package
+	/// manager for its ordering. The archive of every install must be
+	/// available from the sources or a local .deb, as with
+	/// [`Cache::get_archives`].
+	pub fn ordered_actions(&self) -> Result<Vec<(Package<'_>, Op)>, AptErrors> {
+		let actions = unsafe { ordered_actions(&self.ptr, self.depcache(), self.records())? };
+
+		Ok((0..actions.len())
+			.map(|i| {
+				(
+					Package::new(self, unsafe { actions.pkg(i) }),
+					Op::from(actions.action(i)),
+				)
+			})
+			.collect())
+	}
+
+	/// Returns the marked changes in dpkg processing order along with
+	/// the sizes of each version.
+	pub fn install_plan(&self) -> Result<Vec<PlanStep<'_>>, AptErrors> {
+		let mut plan = vec![];
+		for (package, op) in self.ordered_actions()? {
+			let version = match op {
+				Op::Unpack | Op::Configure => package.install_version(),
+				Op::Remove | Op::Purge => package.installed(),
+			};
+			let Some(version) = version else {
+				continue;
+			};
+
+			plan.push(PlanStep {
+				download_size: if op == Op::Unpack { version.size() } else { 0 },
+				installed_size: version.installed_size(),
+				package,
+				version,
+				op,
+			});
+		}
+		Ok(plan)
+	}
+
+	/// Returns the packages that would be newly installed
+	/// to satisfy the `requested` packages.
+	///
+	/// This is the "The following NEW packages will be installed" list
+	/// without the requested packages themselves.
+	/// The marked changes are the same as before this was called.
+	pub fn additional_packages(
+		&self,
+		requested: &[Package],
+	) -> Result<Vec<Package<'_>>, AptErrors> {
+		let snapshot = self.snapshot();
+
+		for pkg in requested {
+			pkg.mark_install(true, true)?;
+		}
+
+		let resolved = self.resolve(false);
+
+		let additional = self
+			.get_changes(true)
+			.filter(|pkg| pkg.marked_new_install())
+			.filter(|pkg| !requested.iter().any(|req| req.index() == pkg.index()))
+			.collect();
+
+		self.restore(&snapshot)?;
+		resolved?;
+		Ok(additional)
+	}
+
+	/// Returns the packages that will no longer be required
+	/// after `apt full-upgrade`.
+	///
+	/// This is the list apt shows as "no longer required" after an upgrade.
+	/// The marks are the same as before this was called.
+	pub fn autoremovable_after_upgrade(&self) -> Result<Vec<Package<'_>>, AptErrors> {
+		let snapshot = self.snapshot();
+		let upgraded = self.upgrade(Upgrade::FullUpgrade);
+
+		// Releasing the action group runs MarkAndSweep so the garbage is current.
+		let mut action_group = unsafe { self.depcache().action_group()? };
+		action_group.pin_mut().release();
+
+		let garbage = self
+			.packages(&PackageSort::default().auto_removable().names())
+			.filter(|pkg| !pkg.marked_delete())
+			.collect();
+
+		self.restore(&snapshot)?;
+		upgraded?;
+		Ok(garbage)
+	}
+
+	/// Get the `Release` information of a source from the downloaded lists.
+	///
+	/// `dist_uri` is the uri the `Release` file is in,
+	/// such as `http://deb.debian.org/debian/dists/bookworm`.
+	/// For a flat repository it's the repository uri followed by its dist,
+	/// such as `file:/srv/repo/./`.
+	///
+	/// Returns [`None`] if `apt update` hasn't downloaded
+	/// a `Release` file for exactly that uri.
+	pub fn release_info(&self, dist_uri: &str) -> Option<ReleaseInfo> {
+		let lists_dir = Config::new().dir("Dir::State::Lists", "/var/lib/apt/lists/");
+		let dist_uri = dist_uri.trim_end_matches('/');
+
+		let content = ["InRelease", "Release"].iter().find_map(|name| {
+			let file = uri_to_lists_filename(&format!("{dist_uri}/{name}"));
+			fs::read_to_string(Path::new(&lists_dir).join(file)).ok()
+		})?;
+
+		// InRelease is clearsigned, the fields are between the armor.
+		let content = match content.strip_prefix("-----BEGIN PGP SIGNED MESSAGE-----") {
+			Some(signed) => {
+				let (_, message) = signed.split_once("\n\n")?;
+				message.split("-----BEGIN PGP SIGNATURE-----").next()?
+			},
+			None => &content,
+		};
+
+		let section = content.trim().split("\n\n").next()?;
+		Some(ReleaseInfo::from(&TagSection::new(section).ok()?))
+	}
+
+	/// Returns the packages marked for installation that conflict with
+	/// or break each other.
+	///
+	/// The first package of each pair is the one that declares the
+	/// [`DepType::Conflicts`] or [`DepType::DpkgBreaks`] on the second.
+	pub fn current_conflicts(&self) -> Vec<(Package<'_>, Package<'_>, DepType)> {
+		let mut conflicts = vec![];
+
+		for pkg in self.get_changes(true).filter(|pkg| pkg.marked_install()) {
+			let Some(ver) = pkg.install_version() else {
+				continue;
+			};
+
+			for dep_type in [DepType::Conflicts, DepType::DpkgBreaks] {
+				let Some(deps) = ver.get_depends(&dep_type) else {
+					continue;
+				};
+
+				for base_dep in deps.iter().flat_map(|dep| dep.iter()) {
+					for target in base_dep.all_targets() {
+						let target_pkg = target.parent();
+						if target_pkg.index() == pkg.index() || !target_pkg.marked_install() {
+							continue;
+						}
+
+						// Only the version that will be installed matters.
+						if target_pkg
+							.install_version()
+							.is_some_and(|install| install.index() == target.index())
+						{
+							conflicts.push((pkg.clone(), target_pkg, dep_type.clone()));
+						}
+					}
+				}
+			}
+		}
+		conflicts
+	}
+
+	/// Returns the packages marked for installation
+	/// whose install version does not come from a trusted source.
+	///
+	/// This is the same check `apt` does before asking
+	/// "Install these packages without verification?"
+	pub fn has_untrusted_changes(&self) -> Vec<Package<'_>> {
+		self.get_changes(true)
+			.filter(|pkg| {
+				pkg.marked_install() && pkg.install_version().is_some_and(|ver| !ver.is_trusted())
+			})
+			.collect()
+	}
 }
 
 /// Iterator Implementation for the Cache.
@@ -722,6 +1847,11 @@ pub(crate) mod raw {
 		/// The returned UniquePtr cannot outlive the cache.
 		unsafe fn find_index(self: &PkgCacheFile, file: &PkgFileIterator) -> UniquePtr<IndexFile>;
 
+		/// The uncompressed URIs of the Translation files of the release
+		/// and component that `file` belongs to, following
+		/// `Acquire::Languages`.
+		pub fn translation_uris(self: &PkgCacheFile, file: &PkgFileIterator) -> Vec<String>;
+
 		/// Return a package by name and optionally architecture.
 		///
 		/// # Safety
@@ -1,7 +1,25 @@
 //! Contains config related structs and functions.
 
+use std::collections::BTreeSet;
+
 use cxx::UniquePtr;
 
+/// Config keys apt has deprecated, paired with what replaces them.
+///
+/// Add new entries here to have [`Config::deprecated_keys`] report them.
+const DEPRECATED_KEYS: &[(&str, &str)] = &[
+	(
+		"APT::Get::Force-Yes",
+		"APT::Get::allow-downgrades, APT::Get::allow-remove-essential, \
+		 APT::Get::allow-change-held-packages",
+	),
+	("APT::Acquire::Translation", "Acquire::Languages"),
+	(
+		"Acquire::http::ProxyAutoDetect",
+		"Acquire::http::Proxy-Auto-Detect",
+	),
+];
+
 /// Struct for Apt Configuration
 ///
 /// All apt configuration methods do not require this struct.
@@ -151,6 +169,94 @@ impl Config {
 		Some(ConfigTree::new(tree))
 	}
 
+	/// Returns every key under `prefix`, sorted.
+	///
+	/// Only keys without children are returned.
+	/// The items of a list share the key of the list.
+	pub fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+		let Some(child) = self.tree(prefix).and_then(|tree| tree.child()) else {
+			return vec![];
+		};
+
+		let mut keys = BTreeSet::new();
+		let mut stack = vec![child];
+		while let Some(node) = stack.pop() {
+			if let Some(sibling) = node.sibling() {
+				stack.push(sibling);
+			}
+
+			if let Some(child) = node.child() {
+				stack.push(child);
+			} else if let Some(tag) = node.full_tag() {
+				// List items don't have a tag of their own.
+				keys.insert(tag.strip_suffix("::").unwrap_or(&tag).to_string());
+			}
+		}
+		keys.into_iter().collect()
+	}
+
+	/// Returns the deprecated keys that are set,
+	/// paired with their recommended replacement.
+	pub fn deprecated_keys(&self) -> Vec<(String, String)> {
+		DEPRECATED_KEYS
+			.iter()
+			.filter(|(key, _)| self.contains(key))
+			.map(|(key, replacement)| (key.to_string(), replacement.to_string()))
+			.collect()
+	}
+
+	/// Return the proxy apt will use for the given `scheme`.
+	///
+	/// This is the same as [`Config::proxy_for_host`] without a host.
+	pub fn proxy_for(&self, scheme: &str) -> Option<String> { self.proxy_for_host(scheme, None) }
+
+	/// Return the proxy apt will use for the given `scheme` and `host`.
+	///
+	/// The resolution order matches apt's http method:
+	///
+	/// 1. `Acquire::{scheme}::Proxy::{host}`
+	/// 2. `Acquire::{scheme}::Proxy`
+	/// 3. The `{scheme}_proxy` environment variable, unless `no_proxy` matches
+	///    the host.
+	///
+	/// `https` falls back to the `http` configuration if it isn't set.
+	/// The keyword `DIRECT` means no proxy and returns [`None`].
+	pub fn proxy_for_host(&self, scheme: &str, host: Option<&str>) -> Option<String> {
+		let schemes: &[&str] = if scheme == "https" { &["https", "http"] } else { &[scheme] };
+		let find = |postfix: &str| {
+			schemes
+				.iter()
+				.find_map(|scheme| self.get(&format!("Acquire::{scheme}::{postfix}")))
+		};
+
+		let proxy = match host.and_then(|host| find(&format!("Proxy::{host}"))) {
+			Some(proxy) => proxy,
+			None => match find("Proxy") {
+				Some(proxy) => proxy,
+				None => {
+					let proxy = std::env::var(format!("{scheme}_proxy")).ok()?;
+					let no_proxy = std::env::var("no_proxy").unwrap_or_default();
+					if let Some(host) = host {
+						let host = host.to_lowercase();
+						if no_proxy
+							.split(',')
+							.map(|domain| domain.trim().to_lowercase())
+							.any(|domain| !domain.is_empty() && host.ends_with(&domain))
+						{
+							return None;
+						}
+					}
+					proxy
+				},
+			},
+		};
+
+		if proxy.is_empty() || proxy == "DIRECT" {
+			return None;
+		}
+		Some(proxy)
+	}
+
 	/// Add strings from a vector into an apt configuration list.
 	///
 	/// If the configuration key is not a list,
@@ -30,24 +30,132 @@
 //!
 //! The Candidate version is what is shown the 'Install Version' field.
 
+use std::pin::Pin;
+
 use cxx::UniquePtr;
 
 use crate::error::AptErrors;
 use crate::progress::OperationProgress;
-use crate::raw::PkgDepCache;
+use crate::raw::{ActionGroup, PkgDepCache, PkgIterator, VerIterator};
 use crate::util::DiskSpace;
 
+/// The message of the error returned when changing a read-only cache.
+pub(crate) const READONLY_ERROR: &str = "The cache was opened read-only";
+
 /// Dependency Extension data for the cache.
 pub struct DepCache {
 	pub(crate) ptr: UniquePtr<PkgDepCache>,
+	pub(crate) readonly: bool,
 }
 
 impl DepCache {
-	pub fn new(ptr: UniquePtr<PkgDepCache>) -> DepCache { DepCache { ptr } }
+	pub fn new(ptr: UniquePtr<PkgDepCache>) -> DepCache {
+		DepCache {
+			ptr,
+			readonly: false,
+		}
+	}
+
+	/// `true` if the cache was opened with
+	/// [`crate::cache::Cache::new_readonly`].
+	///
+	/// Every method that changes the marks returns an error when read-only.
+	pub fn is_readonly(&self) -> bool { self.readonly }
+
+	/// Return an error if the cache was opened read-only.
+	pub(crate) fn check_readonly(&self) -> Result<(), AptErrors> {
+		if self.readonly {
+			return Err(AptErrors::from(READONLY_ERROR.to_string()));
+		}
+		Ok(())
+	}
+
+	/// Mark a package as automatically installed.
+	pub fn mark_auto(&self, pkg: &PkgIterator, mark_auto: bool) -> Result<(), AptErrors> {
+		self.check_readonly()?;
+		self.ptr.mark_auto(pkg, mark_auto);
+		Ok(())
+	}
+
+	/// Mark a package for keep.
+	pub fn mark_keep(&self, pkg: &PkgIterator) -> Result<bool, AptErrors> {
+		self.check_readonly()?;
+		Ok(self.ptr.mark_keep(pkg))
+	}
+
+	/// Mark a package for removal.
+	pub fn mark_delete(&self, pkg: &PkgIterator, purge: bool) -> Result<bool, AptErrors> {
+		self.check_readonly()?;
+		Ok(self.ptr.mark_delete(pkg, purge))
+	}
+
+	/// Mark a package for installation.
+	pub fn mark_install(
+		&self,
+		pkg: &PkgIterator,
+		auto_inst: bool,
+		from_user: bool,
+	) -> Result<bool, AptErrors> {
+		self.check_readonly()?;
+		Ok(self.ptr.mark_install(pkg, auto_inst, from_user))
+	}
+
+	/// Mark a package for reinstallation.
+	pub fn mark_reinstall(&self, pkg: &PkgIterator, reinstall: bool) -> Result<(), AptErrors> {
+		self.check_readonly()?;
+		self.ptr.mark_reinstall(pkg, reinstall);
+		Ok(())
+	}
+
+	/// Set a version to be the candidate of it's package.
+	pub fn set_candidate_version(&self, ver: &VerIterator) -> Result<(), AptErrors> {
+		self.check_readonly()?;
+		self.ptr.set_candidate_version(ver);
+		Ok(())
+	}
+
+	/// Autoinstall every broken package and run the problem resolver.
+	///
+	/// Returns false if the problem resolver fails.
+	pub fn fix_broken(&self) -> Result<bool, AptErrors> {
+		self.check_readonly()?;
+		Ok(self.ptr.fix_broken())
+	}
+
+	/// Return a new [`ActionGroup`] of the current DepCache.
+	///
+	/// # Safety
+	///
+	/// The returned UniquePtr cannot outlive the cache.
+	pub unsafe fn action_group(&self) -> Result<UniquePtr<ActionGroup>, AptErrors> {
+		self.check_readonly()?;
+		Ok(unsafe { self.ptr.action_group() })
+	}
 
 	/// Clear any marked changes in the DepCache.
 	pub fn clear_marked(&self) -> Result<(), AptErrors> {
-		Ok(self.init(OperationProgress::quiet().pin().as_mut())?)
+		self.init(OperationProgress::quiet().pin().as_mut())
+	}
+
+	/// Clear any marked changes in the DepCache, reporting to `progress`.
+	pub fn init(&self, progress: Pin<&mut OperationProgress>) -> Result<(), AptErrors> {
+		self.check_readonly()?;
+		Ok(self.ptr.init(progress)?)
+	}
+
+	/// Perform an Upgrade.
+	///
+	/// ## upgrade_mode:
+	///   * [0] = Remove and install new packages if necessary.
+	///   * [1] = New packages will be installed but nothing will be removed.
+	///   * [3] = Neither remove or install new packages.
+	pub fn upgrade(
+		&self,
+		progress: Pin<&mut OperationProgress>,
+		upgrade_mode: i32,
+	) -> Result<(), AptErrors> {
+		self.check_readonly()?;
+		Ok(self.ptr.upgrade(progress, upgrade_mode)?)
 	}
 
 	/// The amount of space required for installing/removing the packages."
@@ -93,7 +201,7 @@ pub(crate) mod raw {
 		/// use rust_apt::new_cache;
 		///
 		/// let cache = new_cache!().unwrap();
-		/// let mut action_group = unsafe { cache.depcache().action_group() };
+		/// let mut action_group = unsafe { cache.depcache().action_group() }.unwrap();
 		///
 		/// // The C++ deconstructor will be run when the action group leaves scope.
 		/// // You can also call it explicitly.
@@ -6,6 +6,8 @@ use cxx::Exception;
 #[doc(inline)]
 pub use raw::{AptError, empty, pending_error};
 
+use crate::tagfile::ParserError;
+
 #[cxx::bridge]
 pub(crate) mod raw {
 	/// Representation of a single Apt Error or Warning
@@ -100,4 +102,8 @@ impl From<std::io::Error> for AptErrors {
 	fn from(err: std::io::Error) -> Self { AptErrors::from(err.to_string()) }
 }
 
+impl From<ParserError> for AptErrors {
+	fn from(err: ParserError) -> Self { AptErrors::from(err.to_string()) }
+}
+
 impl std::error::Error for AptErrors {}
@@ -141,7 +141,7 @@ impl<'a> BaseDep<'a> {
 	pub fn comp_type(&self) -> Option<&str> { self.ptr.comp_type().ok() }
 
 	// Iterate all Versions that are able to satisfy this dependency
-	pub fn all_targets(&self) -> Vec<Version> {
+	pub fn all_targets(&self) -> Vec<Version<'a>> {
 		unsafe {
 			self.ptr
 				.all_targets()
@@ -150,6 +150,23 @@ impl<'a> BaseDep<'a> {
 				.collect()
 		}
 	}
+
+	/// Return the first target Version that is installed or marked to be
+	/// installed.
+	fn satisfying_version(&self) -> Option<Version<'a>> {
+		unsafe { self.ptr.all_targets() }
+			.iter()
+			.map(|v| Version::new(unsafe { v.unique() }, self.cache))
+			.find(|ver| {
+				ver.parent()
+					.install_version()
+					.is_some_and(|install| install.index() == ver.index())
+			})
+	}
+
+	/// Returns `true` if the dependency is satisfied by what is installed,
+	/// or what is going to be installed.
+	pub fn is_satisfied(&self) -> bool { self.satisfying_version().is_some() }
 }
 
 impl fmt::Display for BaseDep<'_> {
@@ -193,6 +210,17 @@ impl<'a> Dependency<'a> {
 
 	/// Returns a reference to the first BaseDep
 	pub fn first(&self) -> &BaseDep<'a> { &self[0] }
+
+	/// Return the Package that currently satisfies this dependency.
+	///
+	/// The alternatives are checked in order against what is installed,
+	/// or what is marked to be installed.
+	/// For a virtual package this is the package that provides it.
+	pub fn satisfied_by(&self) -> Option<Package<'a>> {
+		self.iter()
+			.find_map(|base_dep| base_dep.satisfying_version())
+			.map(|ver| ver.parent())
+	}
 }
 
 impl fmt::Display for Dependency<'_> {
@@ -119,6 +119,14 @@ pub(crate) mod raw {
 		/// `true` if the PackageFile contains packages that can be downloaded
 		pub fn is_downloadable(self: &PkgFileIterator) -> bool;
 
+		/// `true` if the Release file of the PackageFile sets
+		/// `NotAutomatic: yes`, such as experimental.
+		pub fn is_not_automatic(self: &PkgFileIterator) -> bool;
+
+		/// `true` if the Release file of the PackageFile sets
+		/// `ButAutomaticUpgrades: yes`, such as backports.
+		pub fn is_but_automatic_upgrades(self: &PkgFileIterator) -> bool;
+
 		/// The Index number of the PackageFile
 		#[cxx_name = "Index"]
 		pub fn index(self: &PkgFileIterator) -> u64;
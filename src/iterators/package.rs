@@ -1,10 +1,12 @@
 use std::cell::OnceCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::fmt;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::{fmt, fs};
 
 use cxx::UniquePtr;
 
+use crate::error::AptErrors;
 use crate::raw::{IntoRawIter, PkgIterator};
 use crate::{Cache, DepType, Dependency, Provider, Version, create_depends_map, util};
 /// The state that the user wishes the package to be in.
@@ -30,6 +32,19 @@ impl From<u8> for PkgSelectedState {
 	}
 }
 
+impl fmt::Display for PkgSelectedState {
+	/// The name dpkg uses for this state in its status file.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			PkgSelectedState::Unknown => "unknown",
+			PkgSelectedState::Install => "install",
+			PkgSelectedState::Hold => "hold",
+			PkgSelectedState::DeInstall => "deinstall",
+			PkgSelectedState::Purge => "purge",
+		})
+	}
+}
+
 /// Installation state of the package
 #[derive(Debug, Eq, PartialEq, Hash)]
 pub enum PkgInstState {
@@ -51,6 +66,18 @@ impl From<u8> for PkgInstState {
 	}
 }
 
+impl fmt::Display for PkgInstState {
+	/// The name dpkg uses for this state in its status file.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			PkgInstState::Ok => "ok",
+			PkgInstState::ReInstReq => "reinstreq",
+			PkgInstState::HoldInst => "hold",
+			PkgInstState::HoldReInstReq => "hold-reinstreq",
+		})
+	}
+}
+
 /// The current state of a Package.
 #[derive(Debug, Eq, PartialEq, Hash)]
 pub enum PkgCurrentState {
@@ -80,7 +107,23 @@ impl From<u8> for PkgCurrentState {
 	}
 }
 
-#[derive(Debug)]
+impl fmt::Display for PkgCurrentState {
+	/// The name dpkg uses for this state in its status file.
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			PkgCurrentState::NotInstalled => "not-installed",
+			PkgCurrentState::UnPacked => "unpacked",
+			PkgCurrentState::HalfConfigured => "half-configured",
+			PkgCurrentState::HalfInstalled => "half-installed",
+			PkgCurrentState::ConfigFiles => "config-files",
+			PkgCurrentState::Installed => "installed",
+			PkgCurrentState::TriggersAwaited => "triggers-awaited",
+			PkgCurrentState::TriggersPending => "triggers-pending",
+		})
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Marked {
 	NewInstall,
 	Install,
@@ -94,6 +137,25 @@ pub enum Marked {
 	None,
 }
 
+/// The heuristics checked by [`Package::is_library_package`].
+///
+/// Each field is `true` if that criterion matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LibraryCriteria {
+	/// The package name starts with `lib` and ends with a digit,
+	/// ex: `libc6` but not `libreoffice`.
+	pub name_pattern: bool,
+	/// The section is `libs` and the package isn't a `-dev` package.
+	pub section: bool,
+	/// A soname style virtual package is provided, ex: `libfoo1`.
+	pub soname_provides: bool,
+}
+
+impl LibraryCriteria {
+	/// Returns `true` if any of the criteria matched.
+	pub fn any(&self) -> bool { self.name_pattern || self.section || self.soname_provides }
+}
+
 /// A single unique libapt package.
 pub struct Package<'a> {
 	pub(crate) ptr: UniquePtr<PkgIterator>,
@@ -235,6 +297,18 @@ impl<'a> Package<'a> {
 			.map(|p| Provider::new(p, self.cache))
 	}
 
+	/// Returns the distinct packages that provide this package.
+	///
+	/// [`Package::provides`] can point at the same package more than once
+	/// when several of its versions provide this package.
+	pub fn providing_packages(&self) -> Vec<Package<'a>> {
+		let mut seen = HashSet::new();
+		self.provides()
+			.map(|provider| provider.package())
+			.filter(|pkg| seen.insert(pkg.index()))
+			.collect()
+	}
+
 	/// Check if the package is upgradable.
 	pub fn is_upgradable(&self) -> bool {
 		self.is_installed() && self.cache.depcache().is_upgradable(self)
@@ -332,10 +406,12 @@ impl<'a> Package<'a> {
 	/// ## mark_auto:
 	///   * [true] = Mark the package as automatically installed.
 	///   * [false] = Mark the package as manually installed.
-	pub fn mark_auto(&self, mark_auto: bool) -> bool {
-		self.cache.depcache().mark_auto(self, mark_auto);
+	///
+	/// Returns [`Err`] if the cache was opened read-only.
+	pub fn mark_auto(&self, mark_auto: bool) -> Result<bool, AptErrors> {
+		self.cache.depcache().mark_auto(self, mark_auto)?;
 		// Convert to a bool to remain consistent with other mark functions.
-		true
+		Ok(true)
 	}
 
 	/// # Mark a package for keep.
@@ -343,6 +419,7 @@ impl<'a> Package<'a> {
 	/// ## Returns:
 	///   * [true] if the mark was successful
 	///   * [false] if the mark was unsuccessful
+	///   * [`Err`] if the cache was opened read-only
 	///
 	/// This means that the package will not be changed from its current
 	/// version. This will not stop a reinstall, but will stop removal, upgrades
@@ -351,18 +428,19 @@ impl<'a> Package<'a> {
 	/// We don't believe that there is any reason to unmark packages for keep.
 	/// If someone has a reason, and would like it implemented, please put in a
 	/// feature request.
-	pub fn mark_keep(&self) -> bool { self.cache.depcache().mark_keep(self) }
+	pub fn mark_keep(&self) -> Result<bool, AptErrors> { self.cache.depcache().mark_keep(self) }
 
 	/// # Mark a package for removal.
 	///
 	/// ## Returns:
 	///   * [true] if the mark was successful
 	///   * [false] if the mark was unsuccessful
+	///   * [`Err`] if the cache was opened read-only
 	///
 	/// ## purge:
 	///   * [true] = Configuration files will be removed along with the package.
 	///   * [false] = Only the package will be removed.
-	pub fn mark_delete(&self, purge: bool) -> bool {
+	pub fn mark_delete(&self, purge: bool) -> Result<bool, AptErrors> {
 		self.cache.depcache().mark_delete(self, purge)
 	}
 
@@ -379,12 +457,13 @@ impl<'a> Package<'a> {
 	/// ## Returns:
 	///   * [true] if the mark was successful
 	///   * [false] if the mark was unsuccessful
+	///   * [`Err`] if the cache was opened read-only
 	///
 	/// If a package is already installed, at the latest version,
 	/// and you mark that package for install you will get true,
 	/// but the package will not be altered.
 	/// `pkg.marked_install()` will be false
-	pub fn mark_install(&self, auto_inst: bool, from_user: bool) -> bool {
+	pub fn mark_install(&self, auto_inst: bool, from_user: bool) -> Result<bool, AptErrors> {
 		self.cache
 			.depcache()
 			.mark_install(self, auto_inst, from_user)
@@ -395,16 +474,87 @@ impl<'a> Package<'a> {
 	/// ## Returns:
 	///   * [true] if the mark was successful
 	///   * [false] if the mark was unsuccessful
+	///   * [`Err`] if the cache was opened read-only
 	///
 	/// ## reinstall:
 	///   * [true] = The package will be marked for reinstall.
 	///   * [false] = The package will be unmarked for reinstall.
-	pub fn mark_reinstall(&self, reinstall: bool) -> bool {
-		self.cache.depcache().mark_reinstall(self, reinstall);
+	pub fn mark_reinstall(&self, reinstall: bool) -> Result<bool, AptErrors> {
+		self.cache.depcache().mark_reinstall(self, reinstall)?;
 		// Convert to a bool to remain consistent with other mark functions/
-		true
+		Ok(true)
+	}
+
+	/// Returns the paths dpkg has recorded as installed by this package.
+	///
+	/// These are read from the `.list` file in the dpkg info directory,
+	/// so directories are included along with regular files.
+	/// This will be empty if the package is not installed.
+	pub fn installed_files(&self) -> Vec<PathBuf> {
+		if !self.is_installed() {
+			return vec![];
+		}
+
+		let info_dir = util::dpkg_admin_dir().join("info");
+
+		// Multi-Arch: same packages are listed with their architecture.
+		[
+			format!("{}:{}.list", self.name(), self.arch()),
+			format!("{}.list", self.name()),
+		]
+		.into_iter()
+		.find_map(|name| fs::read(info_dir.join(name)).ok())
+		.map(|list| util::list_file_paths(&list))
+		.unwrap_or_default()
 	}
 
+	/// Check which of the library heuristics match this package.
+	///
+	/// The installed version is checked, or the candidate if not installed.
+	///
+	/// Development packages are left out on purpose, they only hold headers
+	/// and symlinks. So the `libdevel` section doesn't count and neither do
+	/// packages whose name ends with `-dev`.
+	pub fn library_criteria(&self) -> LibraryCriteria {
+		let version = self.installed().or_else(|| self.candidate());
+
+		let name = self.name();
+
+		let section = !name.ends_with("-dev")
+			&& version
+				.as_ref()
+				.and_then(|ver| ver.section().ok())
+				// Sections outside of main look like `contrib/libs`.
+				.map(|section| {
+					section
+						.rsplit_once('/')
+						.map_or(section, |(_, section)| section)
+				})
+				.is_some_and(|section| section == "libs");
+
+		let soname_provides =
+			version.is_some_and(|ver| ver.provides().any(|prv| Self::is_soname(prv.name())));
+
+		LibraryCriteria {
+			name_pattern: name.starts_with("lib") && name.ends_with(|c: char| c.is_ascii_digit()),
+			section,
+			soname_provides,
+		}
+	}
+
+	/// Check if a virtual package name looks like a soname,
+	/// ex: `libfoo1` or `libfoo.so.1`.
+	fn is_soname(name: &str) -> bool {
+		name.starts_with("lib")
+			&& (name.contains(".so") || name.ends_with(|c: char| c.is_ascii_digit()))
+	}
+
+	/// Returns `true` if this looks like a shared library package.
+	///
+	/// This is only a heuristic.
+	/// See [`Package::library_criteria`] for what is checked.
+	pub fn is_library_package(&self) -> bool { self.library_criteria().any() }
+
 	/// Protect a package's state
 	/// for when [`crate::cache::Cache::resolve`] is called.
 	pub fn protect(&self) { self.cache.resolver().protect(self) }
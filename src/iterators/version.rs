@@ -1,17 +1,28 @@
 use std::cell::OnceCell;
 use std::cmp::Ordering;
-use std::collections::HashMap;
-use std::fmt;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use std::{fmt, fs};
 
 use cxx::UniquePtr;
 
+use crate::acquire::raw::download_file;
+use crate::config::Config;
+use crate::error::AptErrors;
+use crate::progress::AcquireProgress;
 use crate::raw::{IntoRawIter, VerIterator};
-use crate::util::cmp_versions;
+use crate::records::RecordField;
+use crate::tagfile::TagSection;
+use crate::util::internal::{compressor_extensions, md5_sum, read_compressed};
+use crate::util::{Bytes, PrivateDir, cmp_versions, parse_version};
 use crate::{
-	Cache, DepType, Dependency, Package, PackageFile, PackageRecords, Provider, VersionFile,
-	create_depends_map,
+	BaseDep, Cache, DepType, Dependency, Package, PackageFile, PackageRecords, Provider,
+	VersionFile, create_depends_map,
 };
 
+/// The hash types a Version can have, strongest first.
+const HASH_TYPES: [&str; 4] = ["sha512", "sha256", "sha1", "md5sum"];
+
 /// Represents a single Version of a package.
 pub struct Version<'a> {
 	pub(crate) ptr: UniquePtr<VerIterator>,
@@ -59,6 +70,17 @@ impl<'a> Version<'a> {
 	/// Return the version's parent package.
 	pub fn parent(&self) -> Package<'a> { Package::new(self.cache, unsafe { self.parent_pkg() }) }
 
+	/// Returns an id for the Version as `name:arch=version`,
+	/// e.g. `apt:amd64=2.4.7`.
+	///
+	/// Unlike the numeric `index()` this is the same across cache rebuilds.
+	/// The arch is that of the parent package, so `Architecture: all`
+	/// versions use the native arch.
+	pub fn canonical_id(&self) -> String {
+		let parent = self.parent();
+		format!("{}:{}={}", parent.name(), parent.arch(), self.version())
+	}
+
 	/// Returns a reference to the Dependency Map owned by the Version
 	///
 	/// Dependencies are in a `Vec<Dependency>`
@@ -138,6 +160,80 @@ impl<'a> Version<'a> {
 	/// Get the translated short description
 	pub fn summary(&self) -> Option<String> { self.desc_lookup()?.short_desc() }
 
+	/// Get the long description, downloading it if it isn't cached.
+	///
+	/// Indexes that use `Description-md5` only carry the summary,
+	/// the long description comes from the `Translation-*` files.
+	/// If those haven't been downloaded, the Translation files of the
+	/// releases this Version comes from are fetched in the order of
+	/// `Acquire::Languages` and checked against the md5.
+	///
+	/// The files are downloaded into a private temporary directory
+	/// that is removed before returning. Nothing is kept between calls,
+	/// run [`Cache::update`] with the language enabled to avoid refetching.
+	///
+	/// Returns [`None`] if none of the sources have the description.
+	pub fn ensure_description(
+		&self,
+		progress: &mut AcquireProgress,
+	) -> Result<Option<String>, AptErrors> {
+		// A cached long description has more than the summary line.
+		if let Some(desc) = self.description().filter(|desc| desc.contains('\n')) {
+			return Ok(Some(desc));
+		}
+
+		let Some(md5) = self.get_record(RecordField::DescriptionMD5) else {
+			return Ok(self.description());
+		};
+
+		let parent = self.parent();
+		let dir = PrivateDir::new()?;
+		let dest_dir = dir.path().to_string_lossy();
+		let extensions = compressor_extensions();
+
+		// Package files of the same release share their Translation files.
+		let mut searched = HashSet::new();
+		let mut downloaded = false;
+		let mut last_err = None;
+
+		for pkg_file in self.package_files().filter(|file| file.is_downloadable()) {
+			for base_uri in self.cache.translation_uris(&pkg_file) {
+				if !searched.insert(base_uri.clone()) {
+					continue;
+				}
+
+				let Some((_, lang)) = base_uri.rsplit_once("Translation-") else {
+					continue;
+				};
+
+				for ext in &extensions {
+					let uri = format!("{base_uri}{ext}");
+					let path = match download_file(&uri, &dest_dir, progress.mut_status()) {
+						Ok(path) => path,
+						Err(err) => {
+							last_err = Some(err);
+							continue;
+						},
+					};
+
+					downloaded = true;
+					let content = read_compressed(&path);
+					fs::remove_file(&path).ok();
+
+					if let Some(desc) = find_translation(&content?, parent.name(), lang, &md5) {
+						return Ok(Some(desc));
+					}
+					break;
+				}
+			}
+		}
+
+		match last_err {
+			Some(err) if !downloaded => Err(err.into()),
+			_ => Ok(None),
+		}
+	}
+
 	/// Get data from the specified record field
 	///
 	/// # Returns:
@@ -163,6 +259,20 @@ impl<'a> Version<'a> {
 			.get_field(field.to_string())
 	}
 
+	/// Get the raw bytes of the specified record field
+	///
+	/// This is the same as [`Version::get_record`],
+	/// but makes no assumption that the field is valid UTF-8.
+	///
+	/// # Returns:
+	///   * Some bytes or None if the field doesn't exist.
+	pub fn get_record_bytes<T: ToString + ?Sized>(&self, field: &T) -> Option<Vec<u8>> {
+		self.version_files()
+			.next()?
+			.lookup()
+			.get_field_bytes(field.to_string())
+	}
+
 	/// Get the hash specified. If there isn't one returns None
 	/// `version.hash("md5sum")`
 	pub fn hash<T: ToString + ?Sized>(&self, hash_type: &T) -> Option<String> {
@@ -180,6 +290,28 @@ impl<'a> Version<'a> {
 	/// This is equivalent to `version.hash("sha512")`
 	pub fn sha512(&self) -> Option<String> { self.hash("sha512") }
 
+	/// Returns the hash types that exist for this version.
+	///
+	/// The types checked are `md5sum`, `sha1`, `sha256` and `sha512`.
+	pub fn available_hashes(&self) -> Vec<String> {
+		HASH_TYPES
+			.iter()
+			.rev()
+			.filter(|hash_type| self.hash(*hash_type).is_some())
+			.map(|hash_type| hash_type.to_string())
+			.collect()
+	}
+
+	/// Get the strongest hash for this version as `(hash_type, hash)`.
+	///
+	/// `sha512` is preferred, then `sha256`, `sha1` and `md5sum`.
+	pub fn strongest_hash(&self) -> Option<(String, String)> {
+		HASH_TYPES.iter().find_map(|hash_type| {
+			self.hash(*hash_type)
+				.map(|hash| (hash_type.to_string(), hash))
+		})
+	}
+
 	/// Returns an Iterator of URIs for the Version.
 	pub fn uris(&self) -> impl Iterator<Item = String> + 'a {
 		self.version_files().filter_map(|v| {
@@ -191,13 +323,128 @@ impl<'a> Version<'a> {
 		})
 	}
 
+	/// Returns `true` if the Version can be downloaded from a trusted source.
+	///
+	/// Only downloadable package files are considered,
+	/// so a Version that only exists in the dpkg status file is not trusted.
+	pub fn is_trusted(&self) -> bool {
+		self.package_files()
+			.any(|pkg_file| pkg_file.is_downloadable() && pkg_file.index_file().is_trusted())
+	}
+
+	/// The size of the .deb file as [`Bytes`].
+	pub fn size_typed(&self) -> Bytes { Bytes(self.size()) }
+
+	/// The uncompressed size of the .deb file as [`Bytes`].
+	pub fn installed_size_typed(&self) -> Bytes { Bytes(self.installed_size()) }
+
+	/// A rough estimate of how long dpkg will take to install this version.
+	///
+	/// This is a heuristic of a fixed time per package plus a time per MiB
+	/// of the installed size. Both are in milliseconds and are read from
+	/// `config`:
+	///
+	///   * `rust_apt::InstallWeight::Package` = The time per package, default
+	///     500.
+	///   * `rust_apt::InstallWeight::MiB` = The time per MiB, default 50.
+	pub fn install_weight(&self, config: &Config) -> Duration {
+		let per_package = config.int("rust_apt::InstallWeight::Package", 500).max(0) as u64;
+		let per_mib = config.int("rust_apt::InstallWeight::MiB", 50).max(0) as u64;
+
+		let mib = self.installed_size() / (1024 * 1024);
+		Duration::from_millis(per_package + per_mib * mib)
+	}
+
+	/// Returns `true` if every source of this Version is `NotAutomatic`.
+	///
+	/// Versions from sources like experimental are never picked
+	/// as the candidate by themselves, even when they are newer.
+	pub fn is_not_automatic(&self) -> bool {
+		let mut files = self
+			.package_files()
+			.filter(|pkg_file| pkg_file.is_downloadable())
+			.peekable();
+
+		files.peek().is_some() && files.all(|pkg_file| pkg_file.is_not_automatic())
+	}
+
+	/// Returns `true` if this Version is newer than `other`
+	/// and bumps the major number of the upstream version.
+	///
+	/// The major number is the leading digits of the upstream version,
+	/// so `2.4.7` to `3.0` is a major upgrade and `2.1` to `2.2` is not.
+	pub fn is_major_upgrade_from(&self, other: &Version) -> bool {
+		let major = |ver: &Version| -> Option<u64> {
+			let upstream = parse_version(ver.version())?.upstream;
+			let end = upstream
+				.find(|c: char| !c.is_ascii_digit())
+				.unwrap_or(upstream.len());
+			upstream[..end].parse().ok()
+		};
+
+		match (major(self), major(other)) {
+			(Some(new), Some(old)) => {
+				new > old && cmp_versions(self.version(), other.version()) == Ordering::Greater
+			},
+			_ => false,
+		}
+	}
+
+	/// Returns the Conflicts and Breaks of this Version that name
+	/// its own package or something it provides.
+	///
+	/// These are often intentional, such as a versioned Breaks on an older
+	/// version of the same package, but can also point to a malformed package.
+	pub fn self_conflicts(&self) -> Vec<BaseDep<'a>> {
+		let parent = self.parent();
+		let provides: Vec<String> = self.provides().map(|prv| prv.name().to_string()).collect();
+
+		let mut conflicts = vec![];
+		for dep_type in [DepType::Conflicts, DepType::DpkgBreaks] {
+			let Some(deps) = self.get_depends(&dep_type) else {
+				continue;
+			};
+
+			for base_dep in deps.iter().flat_map(|dep| dep.iter()) {
+				if base_dep.name() == parent.name()
+					|| provides.iter().any(|prv| prv == base_dep.name())
+				{
+					conflicts.push(base_dep.clone());
+				}
+			}
+		}
+		conflicts
+	}
+
 	/// Set this version as the candidate.
-	pub fn set_candidate(&self) { self.cache.depcache().set_candidate_version(self); }
+	///
+	/// Returns [`Err`] if the cache was opened read-only.
+	pub fn set_candidate(&self) -> Result<(), AptErrors> {
+		self.cache.depcache().set_candidate_version(self)
+	}
 
 	/// The priority of the Version as shown in `apt policy`.
 	pub fn priority(&self) -> i32 { self.cache.priority(self) }
 }
 
+/// Find the `Description-{lang}` of `name` in a Translation file
+/// and make sure that it matches `md5`.
+fn find_translation(content: &str, name: &str, lang: &str, md5: &str) -> Option<String> {
+	content
+		.split("\n\n")
+		// Only parse the sections that could match.
+		.filter(|section| section.contains(md5))
+		.filter_map(|section| TagSection::new(section.trim()).ok())
+		.find(|section| {
+			section.get("Package").is_some_and(|pkg| pkg == name)
+				&& section.get("Description-md5").is_some_and(|sum| sum == md5)
+		})?
+		.get(&format!("Description-{lang}"))
+		// The md5 includes the trailing newline of the field.
+		.filter(|desc| md5_sum(&format!("{desc}\n")) == md5)
+		.cloned()
+}
+
 // Implementations for comparing versions.
 impl PartialEq for Version<'_> {
 	fn eq(&self, other: &Self) -> bool {
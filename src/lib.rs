@@ -33,10 +33,14 @@ pub mod util;
 pub use cache::{Cache, PackageSort};
 pub use iterators::dependency::{BaseDep, DepFlags, DepType, Dependency, create_depends_map};
 pub use iterators::files::{PackageFile, VersionFile};
-pub use iterators::package::{Marked, Package, PkgCurrentState, PkgInstState, PkgSelectedState};
+pub use iterators::package::{
+	LibraryCriteria, Marked, Package, PkgCurrentState, PkgInstState, PkgSelectedState,
+};
 pub use iterators::provider::Provider;
 pub use iterators::version::Version;
 
+pub use crate::acquire::AcqItemType;
+
 /// C++ bindings for libapt-pkg
 pub mod raw {
 	pub use crate::acquire::raw::{
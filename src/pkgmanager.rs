@@ -15,6 +15,7 @@ pub(crate) mod raw {
 
 		type PackageManager;
 		type ProblemResolver;
+		type PkgActions;
 		type OrderResult;
 
 		type PkgCacheFile = crate::cache::raw::PkgCacheFile;
@@ -47,6 +48,30 @@ pub(crate) mod raw {
 		/// This required more work to implement but is the most flexible.
 		pub fn do_install_fd(self: &PackageManager, fd: i32) -> OrderResult;
 
+		/// Order the marked changes the way `do_install` would,
+		/// without running dpkg.
+		///
+		/// # Safety
+		///
+		/// The returned UniquePtr cannot outlive the cache.
+		unsafe fn ordered_actions(
+			cache: &PkgCacheFile,
+			depcache: &PkgDepCache,
+			records: &PkgRecords,
+		) -> Result<UniquePtr<PkgActions>>;
+
+		pub fn len(self: &PkgActions) -> usize;
+
+		/// The package of the action at `index`.
+		///
+		/// # Safety
+		///
+		/// The returned UniquePtr cannot outlive the cache.
+		unsafe fn pkg(self: &PkgActions, index: usize) -> UniquePtr<PkgIterator>;
+
+		/// The action at `index`, see [`crate::cache::Op`].
+		pub fn action(self: &PkgActions, index: usize) -> u8;
+
 		/// # Safety
 		///
 		/// The returned UniquePtr cannot outlive the cache.
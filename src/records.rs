@@ -181,6 +181,10 @@ impl PackageRecords {
 
 	pub fn get_field(&self, field: String) -> Option<String> { self.parser().get_field(field).ok() }
 
+	pub fn get_field_bytes(&self, field: String) -> Option<Vec<u8>> {
+		self.parser().get_field_bytes(field).ok()
+	}
+
 	pub fn hash_find(&self, hash_type: String) -> Option<String> {
 		self.parser().hash_find(hash_type).ok()
 	}
@@ -188,6 +192,32 @@ impl PackageRecords {
 
 type SourceParser<'a> = Ref<'a, UniquePtr<raw::SourceParser>>;
 
+/// An owned copy of a single source record.
+///
+/// Unlike the parser returned by [`SourceRecords::lookup`],
+/// this doesn't change when the records are moved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRecord {
+	pub package: String,
+	pub version: String,
+	pub maintainer: String,
+	pub section: String,
+	/// The full text of the record.
+	pub record: String,
+}
+
+impl From<&raw::SourceParser> for SourceRecord {
+	fn from(parser: &raw::SourceParser) -> Self {
+		SourceRecord {
+			package: parser.package(),
+			version: parser.version(),
+			maintainer: parser.maintainer(),
+			section: parser.section(),
+			record: parser.as_str(),
+		}
+	}
+}
+
 pub struct SourceRecords {
 	ptr: UniquePtr<raw::SourceRecords>,
 	parser: RefCell<UniquePtr<raw::SourceParser>>,
@@ -204,6 +234,33 @@ impl SourceRecords {
 	/// Return all of the parsers to their starting position
 	pub fn restart(&self) { self.ptr.restart() }
 
+	/// Step to the next source record.
+	///
+	/// Returns None and restarts the records once the end is reached.
+	///
+	/// # Example:
+	/// ```
+	/// use rust_apt::new_cache;
+	///
+	/// let cache = new_cache!().unwrap();
+	/// let src_records = cache.source_records().unwrap();
+	///
+	/// while let Some(record) = src_records.step() {
+	///     println!("{}", record.package());
+	/// }
+	/// ```
+	pub fn step(&self) -> Option<SourceParser<'_>> {
+		unsafe {
+			self.parser.replace(self.ptr.step());
+		}
+
+		if self.parser.borrow().end() {
+			self.restart();
+			return None;
+		}
+		Some(self.parser.borrow())
+	}
+
 	/// Lookup the string name of the package.
 	///
 	/// # Example:
@@ -271,6 +328,8 @@ pub(crate) mod raw {
 		pub fn short_desc(self: &Parser) -> Result<String>;
 
 		pub fn get_field(self: &Parser, field: String) -> Result<String>;
+		/// Get the raw bytes of a field, they may not be valid UTF-8.
+		pub fn get_field_bytes(self: &Parser, field: String) -> Result<Vec<u8>>;
 		pub fn hash_find(self: &Parser, hash_type: String) -> Result<String>;
 
 		pub fn archive_uri(self: &IndexFile, filename: &str) -> String;
@@ -280,6 +339,14 @@ pub(crate) mod raw {
 
 		pub fn restart(self: &SourceRecords);
 
+		/// Step to the next source record.
+		///
+		/// # Safety
+		///
+		/// The returned Parser can not out live the records struct.
+		/// Make sure to check the `end()` to see if null.
+		unsafe fn step(self: &SourceRecords) -> UniquePtr<SourceParser>;
+
 		/// # Safety
 		///
 		/// The returned Parser can not out live the records struct.
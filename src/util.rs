@@ -1,10 +1,18 @@
 //! Contains miscellaneous helper utilities.
 use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ffi::OsStr;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::{fmt, fs, io, process};
 
 use terminal_size::{Height, Width, terminal_size};
 
+use crate::config::Config;
 use crate::error::AptErrors;
-use crate::{Cache, DepFlags, Package, config};
+use crate::{Cache, DepFlags, DepType, Package, Version, config};
 
 /// Get the terminal's height, i.e. the number of rows it has.
 ///
@@ -53,7 +61,56 @@ pub fn cmp_versions(ver1: &str, ver2: &str) -> Ordering {
 	}
 }
 
+/// A Debian version split into its parts by [`parse_version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParsedVersion<'a> {
+	/// The epoch, `0` if the version doesn't have one.
+	pub epoch: u32,
+	/// The upstream version.
+	pub upstream: &'a str,
+	/// The Debian revision, if there is one.
+	pub revision: Option<&'a str>,
+}
+
+/// Split a version into `[epoch:]upstream[-revision]`.
+///
+/// Returns [`None`] if the epoch isn't a number or the upstream is empty.
+///
+/// # Examples
+/// ```
+/// use rust_apt::util::parse_version;
+///
+/// let parsed = parse_version("1:2.4.7-1ubuntu2").unwrap();
+///
+/// assert_eq!(parsed.epoch, 1);
+/// assert_eq!(parsed.upstream, "2.4.7");
+/// assert_eq!(parsed.revision, Some("1ubuntu2"));
+/// ```
+pub fn parse_version(version: &str) -> Option<ParsedVersion<'_>> {
+	let (epoch, rest) = match version.split_once(':') {
+		Some((epoch, rest)) => (epoch.parse().ok()?, rest),
+		None => (0, version),
+	};
+
+	// The revision is everything after the last hyphen.
+	let (upstream, revision) = match rest.rsplit_once('-') {
+		Some((upstream, revision)) => (upstream, Some(revision)),
+		None => (rest, None),
+	};
+
+	if upstream.is_empty() {
+		return None;
+	}
+
+	Some(ParsedVersion {
+		epoch,
+		upstream,
+		revision,
+	})
+}
+
 /// Disk Space that `apt` will use for a transaction.
+#[derive(Debug)]
 pub enum DiskSpace {
 	/// Additional Disk Space required.
 	Require(u64),
@@ -102,6 +159,35 @@ pub fn unit_str(val: u64, base: NumSys) -> String {
 	format!("{val} B")
 }
 
+/// A size in bytes that is always formatted the same way.
+///
+/// [`fmt::Display`] uses [`NumSys::Decimal`] like apt does.
+///
+/// ```
+/// use rust_apt::util::Bytes;
+///
+/// assert_eq!(Bytes(1536).to_string(), "1.54 KB");
+/// assert_eq!(Bytes(1536).binary(), "1.50 KiB");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Bytes(pub u64);
+
+impl Bytes {
+	/// Format with [`NumSys::Binary`], ex: `1.50 KiB`.
+	pub fn binary(&self) -> String { unit_str(self.0, NumSys::Binary) }
+
+	/// Format with [`NumSys::Decimal`], ex: `1.54 KB`.
+	pub fn decimal(&self) -> String { unit_str(self.0, NumSys::Decimal) }
+}
+
+impl From<u64> for Bytes {
+	fn from(value: u64) -> Self { Bytes(value) }
+}
+
+impl fmt::Display for Bytes {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.decimal()) }
+}
+
 /// Converts seconds into a human readable time string.
 pub fn time_str(seconds: u64) -> String {
 	if seconds > 60 * 60 * 24 {
@@ -194,6 +280,84 @@ pub fn apt_is_locked() -> bool {
 	raw::apt_is_locked()
 }
 
+/// Checks if another process holds the dpkg lock.
+///
+/// Unlike [`apt_is_locked`] this does not see locks held by the current
+/// process, and it does not require root.
+pub fn dpkg_is_locked() -> bool {
+	config::init_config_system();
+	raw::dpkg_is_locked()
+}
+
+/// The dpkg admin directory, usually `/var/lib/dpkg/`.
+pub(crate) fn dpkg_admin_dir() -> PathBuf {
+	let status = Config::new().file("Dir::State::status", "/var/lib/dpkg/status");
+	Path::new(&status)
+		.parent()
+		.map_or_else(|| PathBuf::from("/var/lib/dpkg/"), Path::to_path_buf)
+}
+
+/// Parse the paths out of a dpkg `.list` file.
+///
+/// Paths are kept as bytes since dpkg doesn't require them to be UTF-8.
+pub(crate) fn list_file_paths(list: &[u8]) -> Vec<PathBuf> {
+	list.split(|byte| *byte == b'\n')
+		// The root directory is listed as /.
+		.filter(|line| !line.is_empty() && *line != b"/.")
+		.map(|line| PathBuf::from(OsStr::from_bytes(line)))
+		.collect()
+}
+
+/// Convert a URI into the file name apt uses for it in `Dir::State::Lists`.
+///
+/// The scheme and any login are dropped, `/` becomes `_`
+/// and other special characters are escaped.
+///
+/// # Example:
+/// ```
+/// use rust_apt::util::uri_to_lists_filename;
+///
+/// assert_eq!(
+///     uri_to_lists_filename("http://deb.debian.org/debian/dists/sid/InRelease"),
+///     "deb.debian.org_debian_dists_sid_InRelease"
+/// );
+/// ```
+pub fn uri_to_lists_filename(uri: &str) -> String { raw::uri_to_lists_filename(uri) }
+
+/// A new directory in the temp dir that only the current user can access.
+///
+/// It is removed along with its contents when dropped.
+pub(crate) struct PrivateDir(PathBuf);
+
+impl PrivateDir {
+	pub(crate) fn new() -> io::Result<PrivateDir> {
+		let base = std::env::temp_dir();
+		let mut err = io::Error::from(io::ErrorKind::AlreadyExists);
+
+		for attempt in 0..100 {
+			let nanos = SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.map_or(0, |time| time.subsec_nanos());
+			let path = base.join(format!("rust-apt-{}-{nanos}-{attempt}", process::id()));
+
+			// mkdir fails if anything is already at the path,
+			// so a planted symlink can't redirect what's written here.
+			match fs::DirBuilder::new().mode(0o700).create(&path) {
+				Ok(()) => return Ok(PrivateDir(path)),
+				Err(e) if e.kind() == io::ErrorKind::AlreadyExists => err = e,
+				Err(e) => return Err(e),
+			}
+		}
+		Err(err)
+	}
+
+	pub(crate) fn path(&self) -> &Path { &self.0 }
+}
+
+impl Drop for PrivateDir {
+	fn drop(&mut self) { fs::remove_dir_all(&self.0).ok(); }
+}
+
 /// Reference implementation to print broken packages just like apt does.
 ///
 /// ## Returns [`None`] if the package is not considered broken
@@ -277,6 +441,86 @@ pub fn show_broken_pkg(cache: &Cache, pkg: &Package, now: bool) -> Option<String
 	Some(broken_string)
 }
 
+/// Render the dependencies of a version as a tree, like `apt-cache depends
+/// --recurse`.
+///
+/// ```text
+/// apt 2.6.1
+///  |- Depends: adduser
+///  |- Depends: gpgv
+///  |  | gpgv2
+///  |- Depends: libapt-pkg6.0 (>= 2.6.1)
+///      |- Depends: libbz2-1.0
+/// ```
+///
+/// Or groups list the alternatives after the first dependency.
+/// Each package is only expanded once, which keeps cycles from recursing.
+///
+/// ## recommends:
+///   * [true] = Recommends are included with PreDepends and Depends.
+///   * [false] = Only PreDepends and Depends are included.
+///
+/// ## max_depth:
+///   * How many levels of dependencies to show. `1` is only the direct
+///     dependencies of `version`.
+pub fn format_depends_tree(version: &Version, recommends: bool, max_depth: usize) -> String {
+	let parent = version.parent();
+	let mut tree = format!("{} {}\n", parent.name(), version.version());
+
+	let mut seen = HashSet::from([parent.index()]);
+	write_depends_tree(&mut tree, version, recommends, max_depth, 0, &mut seen);
+	tree
+}
+
+fn write_depends_tree(
+	tree: &mut String,
+	version: &Version,
+	recommends: bool,
+	max_depth: usize,
+	depth: usize,
+	seen: &mut HashSet<u64>,
+) {
+	if depth >= max_depth {
+		return;
+	}
+
+	let mut dep_types = vec![DepType::PreDepends, DepType::Depends];
+	if recommends {
+		dep_types.push(DepType::Recommends);
+	}
+
+	let indent = "    ".repeat(depth);
+	for dep in dep_types
+		.iter()
+		.filter_map(|dep_type| version.get_depends(dep_type))
+		.flatten()
+	{
+		for (i, base_dep) in dep.iter().enumerate() {
+			if i == 0 {
+				*tree += &format!("{indent} |- {}: ", base_dep.dep_type());
+			} else {
+				*tree += &format!("{indent} |  | ");
+			}
+
+			*tree += base_dep.name();
+			if let (Some(comp), Some(ver_str)) = (base_dep.comp_type(), base_dep.version()) {
+				*tree += &format!(" ({comp} {ver_str})");
+			}
+			*tree += "\n";
+
+			// Only expand packages that haven't been seen before.
+			let target = base_dep.target_package();
+			if depth + 1 >= max_depth || !seen.insert(target.index()) {
+				continue;
+			}
+
+			if let Some(cand) = target.candidate() {
+				write_depends_tree(tree, &cand, recommends, max_depth, depth + 1, seen);
+			}
+		}
+	}
+}
+
 #[cxx::bridge]
 pub(crate) mod raw {
 	unsafe extern "C++" {
@@ -311,5 +555,31 @@ pub(crate) mod raw {
 
 		/// Check if the lockfile is locked.
 		pub fn apt_is_locked() -> bool;
+
+		/// Check if another process holds a lock on the dpkg database.
+		pub fn dpkg_is_locked() -> bool;
+
+		/// Convert a URI into the name apt uses for it in the lists directory.
+		pub fn uri_to_lists_filename(uri: &str) -> String;
+	}
+}
+
+/// Helpers for use inside the crate only.
+///
+/// These are kept out of [`raw`] so they aren't exported as `rust_apt::raw`.
+#[cxx::bridge]
+pub(crate) mod internal {
+	unsafe extern "C++" {
+		include!("rust-apt/apt-pkg-c/util.h");
+
+		/// Read a file, decompressing it based on its extension.
+		fn read_compressed(path: &str) -> Result<String>;
+
+		/// Return the md5 hash of `data` as hex.
+		fn md5_sum(data: &str) -> String;
+
+		/// The file extensions of the compressors apt knows,
+		/// in order of preference, including the empty one.
+		fn compressor_extensions() -> Vec<String>;
 	}
 }
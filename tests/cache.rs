@@ -1,12 +1,22 @@
+mod common;
+
 mod cache {
 	use std::collections::HashMap;
 	use std::fmt::Write as _;
+	use std::path::Path;
+	use std::time::{Duration, Instant};
 
 	use cxx::{CxxVector, UniquePtr};
 	use rust_apt::cache::*;
+	use rust_apt::config::Config;
+	use rust_apt::error::AptErrors;
+	use rust_apt::progress::{AcquireProgress, InstallProgress, OperationProgress};
 	use rust_apt::raw::{IntoRawIter, ItemDesc, create_acquire};
+	use rust_apt::tagfile::parse_tagfile;
 	use rust_apt::util::*;
-	use rust_apt::{DepType, new_cache};
+	use rust_apt::{AcqItemType, DepType, Marked, new_cache};
+
+	use crate::common::with_fixture_status;
 
 	// This is a manual test. I don't know a good way to dynamically test this
 	// Maybe by installing a test-deb with certain depends and checking the
@@ -219,6 +229,17 @@ mod cache {
 		assert!(version.hash("sha1").is_none())
 	}
 
+	#[test]
+	fn strongest_hash() {
+		let cache = new_cache!().unwrap();
+		let version = cache.get("apt").unwrap().candidate().unwrap();
+
+		assert_eq!(version.available_hashes(), vec!["md5sum", "sha256"]);
+		let (hash_type, hash) = version.strongest_hash().unwrap();
+		assert_eq!(hash_type, "sha256");
+		assert_eq!(hash, version.sha256().unwrap());
+	}
+
 	#[test]
 	fn shortname() {
 		let cache = new_cache!().unwrap();
@@ -393,6 +414,44 @@ mod cache {
 		}
 	}
 
+	#[test]
+	fn satisfied_by() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+		let installed = pkg.installed().unwrap();
+
+		// Apt is installed so all of its dependencies should be satisfied.
+		for dep in installed.dependencies().unwrap() {
+			let satisfier = dep.satisfied_by().unwrap();
+			assert!(satisfier.is_installed());
+
+			// The first satisfied alternative is the one that's reported.
+			let base_dep = dep.iter().find(|base_dep| base_dep.is_satisfied()).unwrap();
+			assert!(
+				base_dep
+					.all_targets()
+					.iter()
+					.any(|ver| ver.parent() == satisfier)
+			);
+		}
+
+		// The installed alternative is reported, not the first one.
+		let cache = new_cache!(&["tests/files/cache/or-dep_0.0.1.deb"]).unwrap();
+		let ver = cache.get("or-dep").unwrap().candidate().unwrap();
+		let deps = ver.dependencies().unwrap();
+		assert_eq!(deps.len(), 1);
+		assert!(deps[0].is_or());
+		assert_eq!(deps[0].first().name(), "not-exist");
+		assert_eq!(deps[0].satisfied_by().unwrap().name(), "apt");
+
+		// Nothing is going to satisfy a dependency that doesn't exist.
+		let cache = new_cache!(&["tests/files/cache/broken-or-dep_0.0.1.deb"]).unwrap();
+		let ver = cache.get("broken-or-dep").unwrap().candidate().unwrap();
+		for dep in ver.dependencies().unwrap() {
+			assert!(dep.satisfied_by().is_none());
+		}
+	}
+
 	#[test]
 	fn provides_list() {
 		let cache = new_cache!().unwrap();
@@ -488,6 +547,25 @@ mod cache {
 		}
 	}
 
+	#[test]
+	fn providing_packages() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("www-browser").unwrap();
+
+		let providers = pkg.providing_packages();
+		assert!(!providers.is_empty());
+
+		let mut ids: Vec<_> = providers.iter().map(|pkg| pkg.index()).collect();
+		ids.sort();
+		ids.dedup();
+		assert_eq!(ids.len(), providers.len());
+
+		// Every provider should still be found through provides.
+		for provider in &providers {
+			assert!(pkg.provides().any(|p| p.package() == *provider));
+		}
+	}
+
 	#[test]
 	fn sources() {
 		let cache = new_cache!().unwrap();
@@ -507,6 +585,12 @@ mod cache {
 		for item in uris.iter() {
 			println!("{} = {}", item.uri(), item.owner().dest_file())
 		}
+
+		// The package lists should be in there.
+		assert!(
+			uris.iter()
+				.any(|item| item.item_type() == AcqItemType::Index)
+		);
 	}
 
 	#[test]
@@ -560,12 +644,12 @@ mod cache {
 		let cache = new_cache!().unwrap();
 		let pkg = cache.get("neofetch").unwrap();
 
-		pkg.mark_install(true, true);
+		pkg.mark_install(true, true).unwrap();
 		pkg.protect();
 		cache.resolve(false).unwrap();
 
 		let pkg2 = cache.get("gsasl-common").unwrap();
-		pkg2.mark_install(true, true);
+		pkg2.mark_install(true, true).unwrap();
 		assert!(pkg2.marked_install())
 	}
 
@@ -590,7 +674,7 @@ mod cache {
 		let cache = new_cache!().unwrap();
 		let pkg = cache.get("apt").unwrap();
 
-		pkg.mark_delete(true);
+		pkg.mark_delete(true).unwrap();
 
 		assert!(pkg.marked_delete());
 
@@ -646,7 +730,7 @@ mod cache {
 		.unwrap();
 
 		let pkg = cache.get("dep-pkg1").unwrap();
-		pkg.mark_install(false, false);
+		pkg.mark_install(false, false).unwrap();
 
 		// This package is not installed, only marked
 		assert!(pkg.installed().is_none());
@@ -659,8 +743,8 @@ mod cache {
 		assert!(install_ver.version() == "0.0.2");
 
 		let old_ver = pkg.get_version("0.0.1").unwrap();
-		old_ver.set_candidate();
-		pkg.mark_install(false, false);
+		old_ver.set_candidate().unwrap();
+		pkg.mark_install(false, false).unwrap();
 
 		let install_ver = pkg.install_version().unwrap();
 
@@ -678,7 +762,7 @@ mod cache {
 		// config.set("Debug::pkgProblemResolver", "1");
 
 		pkg.protect();
-		pkg.mark_install(false, true);
+		pkg.mark_install(false, true).unwrap();
 
 		let expected = concat!(
 			" broken-or-dep : Depends: not-exist (>= 3.6.1) but it is not installable or\n",
@@ -696,4 +780,607 @@ mod cache {
 		}
 		println!("{err}");
 	}
+
+	#[test]
+	// This test relies on 'neofetch' not being installed.
+	fn untrusted_changes() {
+		let cache = new_cache!().unwrap();
+
+		// Nothing is marked yet, so nothing can be untrusted.
+		assert!(cache.has_untrusted_changes().is_empty());
+
+		let pkg = cache.get("neofetch").unwrap();
+		pkg.mark_install(true, true).unwrap();
+		pkg.protect();
+		cache.resolve(false).unwrap();
+
+		for pkg in cache.has_untrusted_changes() {
+			assert!(pkg.marked_install());
+			assert!(!pkg.install_version().unwrap().is_trusted());
+		}
+
+		// neofetch should come from a trusted repository.
+		assert!(pkg.install_version().unwrap().is_trusted());
+		assert!(
+			!cache
+				.has_untrusted_changes()
+				.iter()
+				.any(|untrusted| untrusted.name() == "neofetch")
+		);
+	}
+
+	#[test]
+	// This test assumes a healthy system with no package manager running.
+	fn consistency() {
+		let cache = new_cache!().unwrap();
+		let report = cache.check_consistency();
+
+		assert_eq!(report.broken_count, 0);
+		assert!(report.half_configured.is_empty());
+		assert!(!report.locked);
+		assert!(!report.interrupted);
+		assert!(report.is_ok());
+	}
+
+	#[test]
+	fn lockfile() {
+		let cache = new_cache!().unwrap();
+
+		let lockfile = cache.export_lockfile();
+		assert!(lockfile.starts_with("# rust-apt lockfile v1\n"));
+
+		// The same system should verify against its own lockfile.
+		assert!(cache.verify_lockfile(&lockfile).is_empty());
+
+		let apt = cache.get("apt").unwrap();
+		let name = apt.fullname(false);
+		let version = apt.installed().unwrap().version().to_string();
+		let flag = if apt.is_auto_installed() { "auto" } else { "manual" };
+
+		// Change apt's line and everything else should still match.
+		let changed: String = lockfile
+			.lines()
+			.filter(|line| !line.starts_with(&format!("{name}=")))
+			.map(|line| format!("{line}\n"))
+			.chain([format!("{name}=0.0.0 {flag}\n"), "garbage\n".to_string()])
+			.collect();
+
+		let diffs = cache.verify_lockfile(&changed);
+		assert_eq!(
+			diffs,
+			vec![
+				LockDiff::Malformed("garbage".to_string()),
+				LockDiff::Version {
+					name,
+					locked: "0.0.0".to_string(),
+					installed: version,
+				},
+			]
+		);
+	}
+
+	#[test]
+	fn owner_of_file() {
+		let cache = new_cache!().unwrap();
+
+		let dpkg = cache.get("dpkg").unwrap();
+		assert!(
+			dpkg.installed_files()
+				.iter()
+				.any(|file| file == Path::new("/usr/bin/dpkg"))
+		);
+
+		let owner = cache.owner_of_file(Path::new("/usr/bin/dpkg")).unwrap();
+		assert_eq!(owner.name(), "dpkg");
+
+		// Merged /usr paths are found from either side of the symlink.
+		for path in ["/bin/ls", "/usr/bin/ls"] {
+			let owner = cache.owner_of_file(Path::new(path)).unwrap();
+			assert_eq!(owner.name(), "coreutils");
+		}
+
+		assert!(
+			cache
+				.owner_of_file(Path::new("/this/file/doesnt/exist"))
+				.is_none()
+		);
+	}
+
+	#[test]
+	fn snapshot_restore() {
+		let cache = new_cache!().unwrap();
+		let apt = cache.get("apt").unwrap();
+
+		apt.mark_delete(true).unwrap();
+		let snapshot = cache.snapshot();
+
+		cache.depcache().clear_marked().unwrap();
+		assert!(!apt.marked_delete());
+
+		cache.restore(&snapshot).unwrap();
+		assert!(apt.marked_purge());
+	}
+
+	#[test]
+	fn snapshot_restore_keep() {
+		// major-pkg 2.1 is installed in the fixture status file.
+		with_fixture_status(&[], |overrides| {
+			let cache = Cache::new_with_config(
+				overrides,
+				&[
+					"tests/files/cache/major-pkg_2.2.deb",
+					"tests/files/cache/major-pkg_3.0.deb",
+				],
+			)
+			.unwrap();
+
+			// Hold major-pkg back at a candidate it wouldn't pick by itself.
+			let pkg = cache.get("major-pkg").unwrap();
+			pkg.get_version("2.2").unwrap().set_candidate().unwrap();
+			pkg.mark_keep().unwrap();
+			assert!(pkg.marked_held());
+			cache
+				.get("conflict-pkg1")
+				.unwrap()
+				.mark_delete(false)
+				.unwrap();
+
+			let changes = |cache: &Cache| -> Vec<(String, Marked)> {
+				cache
+					.get_changes(true)
+					.map(|pkg| (pkg.fullname(false), pkg.marked()))
+					.collect()
+			};
+			let before = changes(&cache);
+			let snapshot = cache.snapshot();
+
+			cache.upgrade(Upgrade::FullUpgrade).unwrap();
+			assert!(pkg.marked_upgrade());
+
+			cache.restore(&snapshot).unwrap();
+			assert_eq!(changes(&cache), before);
+			assert!(pkg.marked_held());
+			assert_eq!(pkg.candidate().unwrap().version(), "2.2");
+		});
+	}
+
+	#[test]
+	// This test relies on 'neofetch' not being installed.
+	fn additional_packages() {
+		let cache = new_cache!(&["tests/files/cache/dep-pkg2_0.0.1.deb"]).unwrap();
+		let requested = [cache.get("dep-pkg2").unwrap()];
+
+		let additional = cache.additional_packages(&requested).unwrap();
+		assert!(additional.iter().any(|add| add.name() == "neofetch"));
+		assert!(!additional.iter().any(|add| add.name() == "dep-pkg2"));
+
+		// The marks should be put back the way they were.
+		assert!(!requested[0].marked_install());
+	}
+
+	#[test]
+	fn dist_upgrade() {
+		// Both conflict packages are installed in the fixture status file.
+		// major-pkg is too, but there's nothing to upgrade it to.
+		// conflict-pkg2 is essential, so conflict-pkg1 has to be removed.
+		with_fixture_status(&[], |overrides| {
+			let cache = Cache::new_with_config::<&str>(overrides, &[]).unwrap();
+			let err = cache.dist_upgrade(false).unwrap_err();
+			let msgs: Vec<_> = err.iter().map(|err| err.msg.as_str()).collect();
+			assert_eq!(
+				msgs,
+				["The following packages would be removed: conflict-pkg1"]
+			);
+			// Nothing is left marked when the removal isn't allowed.
+			assert_eq!(cache.get_changes(false).count(), 0);
+
+			let summary = cache.dist_upgrade(true).unwrap();
+			let removed: Vec<_> = summary.remove.iter().map(|pkg| pkg.name()).collect();
+			assert_eq!(removed, ["conflict-pkg1"]);
+			assert!(cache.get("conflict-pkg1").unwrap().marked_delete());
+		});
+	}
+
+	#[test]
+	fn library_package() {
+		let cache = new_cache!().unwrap();
+
+		let lib = cache.get("libapt-pkg6.0").unwrap();
+		assert!(lib.is_library_package());
+		assert!(lib.library_criteria().name_pattern);
+
+		let apt = cache.get("apt").unwrap();
+		assert!(!apt.is_library_package());
+
+		// Starting with lib isn't enough, nor is being the -dev package.
+		for name in ["libreoffice", "libvirt-daemon", "libapt-pkg-dev"] {
+			let pkg = cache.get(name).unwrap();
+			assert!(
+				!pkg.is_library_package(),
+				"{name}: {:?}",
+				pkg.library_criteria()
+			);
+		}
+	}
+
+	#[test]
+	fn autoremovable_after_upgrade() {
+		let cache = new_cache!().unwrap();
+
+		let garbage = cache.autoremovable_after_upgrade().unwrap();
+		assert!(garbage.iter().all(|pkg| pkg.is_auto_installed()));
+
+		// The upgrade marks are not left behind.
+		assert_eq!(cache.get_changes(false).count(), 0);
+	}
+
+	#[test]
+	fn release_info() {
+		let cache = new_cache!().unwrap();
+		let cand = cache.get("apt").unwrap().candidate().unwrap();
+
+		// Use the site of the main source apt comes from.
+		let pkg_file = cand
+			.package_files()
+			.find(|pkg_file| pkg_file.is_downloadable())
+			.unwrap();
+		let base = pkg_file.index_file().archive_uri("");
+
+		// sources.list may name the dist by either its suite or codename.
+		let dists: Vec<_> = [pkg_file.archive(), pkg_file.codename()]
+			.into_iter()
+			.flatten()
+			.map(|dist| format!("{base}dists/{dist}"))
+			.collect();
+		let info = dists
+			.iter()
+			.find_map(|dist| cache.release_info(dist))
+			.unwrap();
+		dbg!(&info);
+		assert!(info.suite.is_some() || info.codename.is_some());
+
+		// Only the exact uri of the dist matches.
+		assert!(cache.release_info(&base).is_none());
+		assert!(cache.release_info(pkg_file.site().unwrap()).is_none());
+		assert!(
+			cache
+				.release_info(&format!("{}-doesnt-exist", dists[0]))
+				.is_none()
+		);
+	}
+
+	#[test]
+	fn estimated_transaction_time() {
+		let cache = new_cache!(&["tests/files/cache/dep-pkg1_0.0.1.deb"]).unwrap();
+		assert!(cache.estimated_transaction_time(1024).is_zero());
+
+		let pkg = cache.get("dep-pkg1").unwrap();
+		pkg.mark_install(true, true).unwrap();
+
+		let estimate = cache.estimated_transaction_time(1024);
+		assert!(!estimate.is_zero());
+
+		// A faster download can only be quicker.
+		assert!(cache.estimated_transaction_time(u64::MAX) <= estimate);
+
+		// The weights come from the config.
+		let config = Config::new();
+		config.set("rust_apt::InstallWeight::Package", "1000");
+		config.set("rust_apt::InstallWeight::MiB", "0");
+		let ver = pkg.install_version().unwrap();
+		assert_eq!(ver.install_weight(&config), Duration::from_secs(1));
+		let changes = cache.get_changes(false).count() as u64;
+		assert_eq!(
+			cache.estimated_transaction_time(0),
+			Duration::from_secs(changes)
+		);
+		config.clear("rust_apt::InstallWeight");
+	}
+
+	#[test]
+	fn current_conflicts() {
+		let cache = new_cache!(&[
+			"tests/files/cache/conflict-pkg1_0.0.1.deb",
+			"tests/files/cache/conflict-pkg2_0.0.1.deb",
+		])
+		.unwrap();
+
+		let pkg1 = cache.get("conflict-pkg1").unwrap();
+		let pkg2 = cache.get("conflict-pkg2").unwrap();
+
+		pkg1.mark_install(false, true).unwrap();
+		assert!(cache.current_conflicts().is_empty());
+
+		pkg2.mark_install(false, true).unwrap();
+		let conflicts = cache.current_conflicts();
+
+		assert!(conflicts.iter().any(|(pkg, target, dep_type)| {
+			pkg == &pkg1 && target == &pkg2 && *dep_type == DepType::Conflicts
+		}));
+		assert!(conflicts.iter().any(|(pkg, target, dep_type)| {
+			pkg == &pkg2 && target == &pkg1 && *dep_type == DepType::DpkgBreaks
+		}));
+	}
+
+	#[test]
+	fn readonly() {
+		let cache = Cache::new_readonly::<&str>(&[]).unwrap();
+		assert!(cache.depcache().is_readonly());
+
+		let readonly = |err: AptErrors| {
+			assert!(
+				err.to_string().contains("The cache was opened read-only"),
+				"{err}"
+			)
+		};
+
+		let pkg = cache.get("neofetch").unwrap();
+		readonly(pkg.mark_install(true, true).unwrap_err());
+		assert!(!pkg.marked_install());
+		readonly(pkg.mark_auto(true).unwrap_err());
+		readonly(pkg.mark_keep().unwrap_err());
+		readonly(pkg.candidate().unwrap().set_candidate().unwrap_err());
+
+		let apt = cache.get("apt").unwrap();
+		readonly(apt.mark_delete(true).unwrap_err());
+		assert!(!apt.marked_delete());
+		readonly(apt.mark_reinstall(true).unwrap_err());
+		assert!(!apt.marked_reinstall());
+
+		readonly(cache.depcache().clear_marked().unwrap_err());
+		// The DepCache wrappers are guarded too, not just the Cache methods.
+		let mut progress = OperationProgress::quiet();
+		readonly(cache.depcache().init(progress.pin()).unwrap_err());
+		readonly(cache.depcache().upgrade(progress.pin(), 0).unwrap_err());
+		readonly(unsafe { cache.depcache().action_group() }.err().unwrap());
+		readonly(cache.fix_broken().unwrap_err());
+		readonly(cache.resolve(false).unwrap_err());
+		readonly(cache.upgrade(Upgrade::FullUpgrade).unwrap_err());
+
+		let snapshot = cache.snapshot();
+		readonly(cache.restore(&snapshot).unwrap_err());
+		readonly(cache.apply_manifest(&cache.export_manifest()).unwrap_err());
+	}
+
+	#[test]
+	fn not_automatic() {
+		let cache = new_cache!().unwrap();
+		let cand = cache.get("apt").unwrap().candidate().unwrap();
+		assert!(!cand.is_not_automatic());
+
+		// Skip the rest if there isn't a source like experimental configured.
+		let Some(ver) = cache
+			.iter()
+			.flat_map(|pkg| pkg.versions().collect::<Vec<_>>())
+			.find(|ver| {
+				let mut files = ver.package_files().filter(|file| file.is_downloadable());
+				files.next().is_some_and(|file| file.is_not_automatic()) && files.next().is_none()
+			})
+		else {
+			return;
+		};
+
+		assert!(ver.is_not_automatic());
+	}
+
+	#[test]
+	fn plan_diff() {
+		let cache = new_cache!().unwrap();
+		let apt = cache.get("apt").unwrap();
+
+		let before = cache.snapshot();
+		assert!(cache.plan_diff(&before).is_empty());
+
+		apt.mark_delete(false).unwrap();
+		let diff = cache.plan_diff(&before);
+		assert_eq!(diff.marked.len(), 1);
+		assert_eq!(diff.marked[0].0, apt);
+		assert_eq!(diff.marked[0].1, Marked::Remove);
+		assert!(diff.unmarked.is_empty());
+
+		let before = cache.snapshot();
+		cache.depcache().clear_marked().unwrap();
+		let diff = cache.plan_diff(&before);
+		assert!(diff.marked.is_empty());
+		assert_eq!(diff.unmarked, vec![apt]);
+	}
+
+	#[test]
+	fn virtual_packages() {
+		let cache = new_cache!().unwrap();
+		let virtuals = cache.virtual_packages();
+
+		let (_, providers) = virtuals
+			.iter()
+			.find(|(pkg, _)| pkg.name() == "www-browser")
+			.unwrap();
+		assert!(!providers.is_empty());
+
+		for (pkg, providers) in &virtuals {
+			assert!(!pkg.has_versions());
+			assert!(!providers.is_empty());
+		}
+	}
+
+	#[test]
+	fn priority() {
+		let cache = new_cache!().unwrap();
+		let sort = PackageSort::default().priority(Priority::Required);
+		let required: Vec<String> = cache
+			.packages(&sort)
+			.map(|pkg| pkg.name().to_string())
+			.collect();
+
+		assert!(required.iter().any(|name| name == "dpkg"));
+		assert!(required.iter().any(|name| name == "libc6"));
+
+		assert_eq!(Priority::from_name("optional"), Some(Priority::Optional));
+		assert_eq!(Priority::from_name("nonsense"), None);
+	}
+
+	#[test]
+	fn canonical_id() {
+		let cache = new_cache!().unwrap();
+		let pkg = cache.get("apt").unwrap();
+		let cand = pkg.candidate().unwrap();
+
+		assert_eq!(
+			cand.canonical_id(),
+			format!("apt:{}={}", pkg.arch(), cand.version())
+		);
+	}
+
+	#[test]
+	fn install_plan() {
+		let cache = new_cache!(&["tests/files/cache/dep-pkg2_0.0.1.deb"]).unwrap();
+		let pkg = cache.get("dep-pkg2").unwrap();
+		pkg.mark_install(true, true).unwrap();
+		pkg.protect();
+		cache.resolve(true).unwrap();
+
+		let plan = cache.install_plan().unwrap();
+		let position = |name: &str, op| {
+			plan.iter()
+				.position(|step| step.package.name() == name && step.op == op)
+				.unwrap()
+		};
+
+		let unpack = &plan[position("neofetch", Op::Unpack)];
+		assert!(unpack.download_size > 0);
+		assert!(unpack.installed_size > 0);
+
+		// It has to be unpacked before it's configured.
+		assert!(position("neofetch", Op::Unpack) < position("neofetch", Op::Configure));
+		// Dependencies are configured before the packages that depend on them.
+		assert!(position("neofetch", Op::Configure) < position("dep-pkg2", Op::Configure));
+	}
+
+	#[test]
+	fn available_upgrades() {
+		let cache = new_cache!().unwrap();
+		let upgrades = cache.available_upgrades();
+
+		assert_eq!(
+			upgrades.len(),
+			cache.packages(&PackageSort::default().upgradable()).count()
+		);
+		for upgrade in &upgrades {
+			assert!(upgrade.candidate > upgrade.installed);
+		}
+	}
+
+	#[test]
+	fn self_conflicts() {
+		let cache = new_cache!(&["tests/files/cache/self-breaks_0.0.1.deb"]).unwrap();
+		let ver = cache.get("self-breaks").unwrap().candidate().unwrap();
+
+		let conflicts = ver.self_conflicts();
+		assert_eq!(conflicts.len(), 1);
+		assert_eq!(conflicts[0].name(), "self-breaks");
+		assert_eq!(conflicts[0].dep_type(), DepType::DpkgBreaks);
+
+		let apt = cache.get("apt").unwrap().candidate().unwrap();
+		assert!(apt.self_conflicts().is_empty());
+	}
+
+	#[test]
+	fn export_manifest() {
+		let cache = new_cache!().unwrap();
+		let manifest = cache.export_manifest();
+		let sections = parse_tagfile(&manifest).unwrap();
+
+		let apt = sections
+			.iter()
+			.find(|section| section.get("Package").is_some_and(|name| name == "apt"))
+			.unwrap();
+		let pkg = cache.get("apt").unwrap();
+
+		assert_eq!(
+			apt.get("Version").unwrap(),
+			pkg.installed().unwrap().version()
+		);
+		assert_eq!(apt.get("Architecture").unwrap(), pkg.arch());
+		assert!(apt.get("Status").unwrap().ends_with(" ok installed"));
+		assert!(apt.get("Auto-Installed").is_some());
+		assert_eq!(
+			sections.len(),
+			cache.packages(&PackageSort::default().installed()).count()
+		);
+	}
+
+	#[test]
+	fn apply_manifest() {
+		let cache = new_cache!().unwrap();
+		let version = cache
+			.get("neofetch")
+			.unwrap()
+			.candidate()
+			.unwrap()
+			.version()
+			.to_string();
+
+		let manifest = [
+			format!("Package: neofetch\nVersion: {version}\nAuto-Installed: yes\n"),
+			"Package: rust-apt-missing\nVersion: 1.0\n".to_string(),
+			"Package: apt\nVersion: 0.0.0-missing\n".to_string(),
+		]
+		.join("\n");
+		let report = cache.apply_manifest(&manifest).unwrap();
+
+		let pkg = cache.get("neofetch").unwrap();
+		assert!(pkg.marked_install());
+		assert!(pkg.is_auto_installed());
+
+		assert_eq!(report.missing, vec!["rust-apt-missing"]);
+		assert_eq!(
+			report.missing_versions,
+			vec![("apt".to_string(), "0.0.0-missing".to_string())]
+		);
+
+		assert!(cache.apply_manifest("Not a manifest").is_err());
+	}
+
+	#[test]
+	fn major_upgrade() {
+		let cache = new_cache!(&[
+			"tests/files/cache/major-pkg_2.1.deb",
+			"tests/files/cache/major-pkg_2.2.deb",
+			"tests/files/cache/major-pkg_3.0.deb",
+		])
+		.unwrap();
+		let pkg = cache.get("major-pkg").unwrap();
+		let ver = |version| pkg.get_version(version).unwrap();
+
+		assert!(ver("3.0").is_major_upgrade_from(&ver("2.1")));
+		assert!(!ver("2.2").is_major_upgrade_from(&ver("2.1")));
+		// A downgrade is never a major upgrade.
+		assert!(!ver("2.1").is_major_upgrade_from(&ver("3.0")));
+	}
+
+	#[test]
+	fn commit_lock_no_retry() {
+		// Without root the lock can never be taken, so waiting is pointless.
+		if apt_lock().is_ok() {
+			apt_unlock();
+			return;
+		}
+
+		let cache = new_cache!().unwrap();
+		let options = CommitOptions {
+			lock_retry: Some(LockRetry {
+				attempts: 100,
+				delay: Duration::from_secs(1),
+			}),
+		};
+
+		let start = Instant::now();
+		let mut progress = AcquireProgress::quiet();
+		let mut inst_progress = InstallProgress::apt();
+		assert!(
+			cache
+				.commit_with_options(&mut progress, &mut inst_progress, &options)
+				.is_err()
+		);
+		assert!(start.elapsed() < Duration::from_secs(5));
+	}
 }
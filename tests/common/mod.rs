@@ -0,0 +1,43 @@
+//! Helpers shared by the integration tests.
+
+use std::collections::HashMap;
+use std::{fs, panic};
+
+use rust_apt::config::Config;
+
+/// Run `test` with `Dir::State::status` pointed at `tests/files/status`,
+/// along with any `extra` config, and then put the config back,
+/// even if `test` panics.
+///
+/// `test` is given the overrides to pass to `Cache::new_with_config`.
+pub fn with_fixture_status(extra: &[(&str, &str)], test: impl FnOnce(HashMap<String, String>)) {
+	let config = Config::new();
+	let status = fs::canonicalize("tests/files/status").unwrap();
+
+	let mut overrides = HashMap::from([(
+		"Dir::State::status".to_string(),
+		status.display().to_string(),
+	)]);
+	for (key, value) in extra {
+		overrides.insert(key.to_string(), value.to_string());
+	}
+
+	let saved: Vec<_> = overrides
+		.keys()
+		.map(|key| (key.clone(), config.find(key, "")))
+		.collect();
+
+	let result = panic::catch_unwind(panic::AssertUnwindSafe(|| test(overrides)));
+
+	for (key, value) in saved {
+		if value.is_empty() {
+			config.clear(&key);
+		} else {
+			config.set(&key, &value);
+		}
+	}
+
+	if let Err(err) = result {
+		panic::resume_unwind(err);
+	}
+}
@@ -1,10 +1,15 @@
+mod common;
+
 mod config {
 	use std::collections::VecDeque;
 	use std::process::Command;
 
+	use rust_apt::cache::{Cache, PackageSort};
 	use rust_apt::config::Config;
 	use rust_apt::new_cache;
 
+	use crate::common::with_fixture_status;
+
 	#[test]
 	fn clear() {
 		// Test to make sure that the config populates properly.
@@ -40,6 +45,80 @@ mod config {
 		println!("{}", config.dump())
 	}
 
+	#[test]
+	fn cache_with_config() {
+		// Only the conflict packages and major-pkg are installed
+		// in the fixture status file.
+		with_fixture_status(&[("rust_apt::Override", "Exists!")], |overrides| {
+			let cache = Cache::new_with_config::<&str>(overrides, &[]).unwrap();
+			let installed: Vec<_> = cache
+				.packages(&PackageSort::default().installed().names())
+				.map(|pkg| pkg.name().to_string())
+				.collect();
+			assert_eq!(installed, ["conflict-pkg1", "conflict-pkg2", "major-pkg"]);
+			assert!(!cache.get("apt").unwrap().is_installed());
+
+			let config = Config::new();
+			assert_eq!(config.find("rust_apt::Override", "None"), "Exists!");
+		});
+	}
+
+	#[test]
+	fn proxy() {
+		let config = Config::new();
+
+		config.set("Acquire::http::Proxy", "http://proxy.example:3142");
+		assert_eq!(
+			config.proxy_for("http").unwrap(),
+			"http://proxy.example:3142"
+		);
+
+		// https falls back to the http proxy.
+		assert_eq!(
+			config.proxy_for("https").unwrap(),
+			"http://proxy.example:3142"
+		);
+
+		// Per host overrides come first.
+		config.set("Acquire::http::Proxy::deb.debian.org", "DIRECT");
+		assert!(
+			config
+				.proxy_for_host("http", Some("deb.debian.org"))
+				.is_none()
+		);
+		assert_eq!(
+			config.proxy_for_host("http", Some("example.com")).unwrap(),
+			"http://proxy.example:3142"
+		);
+
+		config.clear("Acquire::http::Proxy");
+	}
+
+	#[test]
+	fn keys_with_prefix() {
+		let config = Config::new();
+
+		config.set("rust_apt::test::b", "2");
+		config.set("rust_apt::test::a", "1");
+		config.set("rust_apt::test::c::d", "3");
+
+		assert_eq!(
+			config.keys_with_prefix("rust_apt::test"),
+			vec![
+				"rust_apt::test::a",
+				"rust_apt::test::b",
+				"rust_apt::test::c::d",
+			]
+		);
+		assert!(
+			config
+				.keys_with_prefix("rust_apt::doesnt::exist")
+				.is_empty()
+		);
+
+		config.clear("rust_apt::test");
+	}
+
 	#[test]
 	fn find_and_set() {
 		let config = Config::new_clear();
@@ -153,4 +232,20 @@ mod config {
 			}
 		}
 	}
+
+	#[test]
+	fn deprecated_keys() {
+		let config = Config::new();
+		let key = "APT::Acquire::Translation";
+		assert!(!config.deprecated_keys().iter().any(|(k, _)| k == key));
+
+		config.set(key, "en");
+		assert!(
+			config
+				.deprecated_keys()
+				.contains(&(key.to_string(), "Acquire::Languages".to_string()))
+		);
+
+		config.clear(key);
+	}
 }
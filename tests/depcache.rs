@@ -8,14 +8,14 @@ mod depcache {
 		let pkg = cache.get("apt").unwrap();
 
 		dbg!(pkg.marked_reinstall());
-		dbg!(pkg.mark_reinstall(true));
+		dbg!(pkg.mark_reinstall(true).unwrap());
 		assert!(pkg.marked_reinstall());
 	}
 
 	#[test]
 	fn action_groups() {
 		let cache = new_cache!().unwrap();
-		let mut action_group = unsafe { cache.depcache().action_group() };
+		let mut action_group = unsafe { cache.depcache().action_group().unwrap() };
 
 		// This is unsafe due to SIGABRT if you try to release after dropping the cache.
 		// Probably should get wrapped and have a lifetime related to the Cache.
@@ -29,6 +29,21 @@ mod records {
 		assert_eq!(cand.get_record("SHA256"), cand.sha256());
 	}
 
+	#[test]
+	fn field_bytes() {
+		let cache = new_cache!().unwrap();
+		let cand = cache.get("apt").unwrap().candidate().unwrap();
+
+		let bytes = cand.get_record_bytes(RecordField::Maintainer).unwrap();
+		assert_eq!(
+			String::from_utf8(bytes).unwrap(),
+			cand.get_record(RecordField::Maintainer).unwrap()
+		);
+
+		// Apt should not have a homepage
+		assert!(cand.get_record_bytes(RecordField::Homepage).is_none());
+	}
+
 	#[test]
 	fn source() {
 		let cache = new_cache!().unwrap();
@@ -54,4 +69,25 @@ mod records {
 		);
 		dbg!(cache.get("libgc-dev").unwrap().changelog_uri().unwrap());
 	}
+
+	#[test]
+	fn source_packages() {
+		let cache = new_cache!().unwrap();
+
+		let source_pkgs = cache.source_packages();
+		assert!(source_pkgs.iter().any(|name| name == "apt"));
+
+		// The names should be sorted and distinct.
+		assert!(source_pkgs.windows(2).all(|names| names[0] < names[1]));
+
+		let record = cache.get_source_record("apt").unwrap();
+		assert_eq!(record.package, "apt");
+		assert!(record.record.contains("Package: apt"));
+
+		assert!(
+			cache
+				.get_source_record("this-source-doesnt-exist")
+				.is_none()
+		);
+	}
 }
@@ -1,9 +1,14 @@
 mod root {
+	use std::process::Command;
+	use std::time::Duration;
+	use std::{fs, panic, thread};
+
+	use rust_apt::cache::{CommitOptions, LockRetry};
 	use rust_apt::config::Config;
-	use rust_apt::new_cache;
 	use rust_apt::progress::{AcquireProgress, DynAcquireProgress, InstallProgress};
 	use rust_apt::raw::{AcqTextStatus, ItemDesc, ItemState, PkgAcquire};
 	use rust_apt::util::*;
+	use rust_apt::{AcqItemType, new_cache};
 
 	#[test]
 	fn lock() {
@@ -97,6 +102,167 @@ mod root {
 		cache.update(&mut progress).unwrap();
 	}
 
+	#[test]
+	fn update_item_types() {
+		struct Progress<'a> {
+			types: &'a mut Vec<AcqItemType>,
+		}
+
+		impl DynAcquireProgress for Progress<'_> {
+			fn pulse_interval(&self) -> usize { 0 }
+
+			fn hit(&mut self, item: &ItemDesc) { self.types.push(item.item_type()) }
+
+			fn fetch(&mut self, item: &ItemDesc) { self.types.push(item.item_type()) }
+
+			fn done(&mut self, _item: &ItemDesc) {}
+
+			fn start(&mut self) {}
+
+			fn stop(&mut self, _status: &AcqTextStatus) {}
+
+			fn fail(&mut self, _item: &ItemDesc) {}
+
+			fn pulse(&mut self, _status: &AcqTextStatus, _owner: &PkgAcquire) {}
+		}
+
+		// The local repository gets a fresh lists directory,
+		// so the Packages index has to be fetched.
+		with_local_repo(true, || {
+			let mut types = vec![];
+			let cache = new_cache!().unwrap();
+			let mut progress = AcquireProgress::new(Progress { types: &mut types });
+			cache.update(&mut progress).unwrap();
+			drop(progress);
+
+			assert!(types.contains(&AcqItemType::Release));
+			assert!(types.contains(&AcqItemType::Index));
+			assert!(!types.contains(&AcqItemType::Package));
+		});
+	}
+
+	/// Point apt at the local repository in `tests/files/repo` with its own
+	/// lists directory, run `test` and then put the config back.
+	///
+	/// The repository isn't signed. If `trusted` is false it is still used,
+	/// but its packages are not trusted.
+	fn with_local_repo(trusted: bool, test: impl FnOnce()) {
+		let config = Config::new();
+		let repo = fs::canonicalize("tests/files/repo").unwrap();
+		let dir = std::env::temp_dir().join(format!("rust-apt-repo-{}", std::process::id()));
+		let lists = dir.join("lists");
+		fs::create_dir_all(lists.join("partial")).unwrap();
+
+		let sources = dir.join("sources.list");
+		fs::write(
+			&sources,
+			format!(
+				"deb {}file:{} ./\n",
+				if trusted { "[trusted=yes] " } else { "" },
+				repo.display()
+			),
+		)
+		.unwrap();
+
+		let keys = [
+			("Dir::Etc::sourcelist", sources.to_str().unwrap()),
+			("Dir::Etc::sourceparts", "/nonexistent"),
+			("Dir::State::lists", lists.to_str().unwrap()),
+			("Dir::Cache::pkgcache", ""),
+			("Dir::Cache::srcpkgcache", ""),
+			("Acquire::Languages", "en"),
+			("Acquire::AllowInsecureRepositories", "true"),
+		];
+		let saved: Vec<_> = keys
+			.iter()
+			.map(|(key, _)| (*key, config.find(key, "")))
+			.collect();
+		for (key, value) in keys {
+			config.set(key, value);
+		}
+
+		let result = panic::catch_unwind(panic::AssertUnwindSafe(test));
+
+		for (key, value) in saved {
+			if value.is_empty() {
+				config.clear(key);
+			} else {
+				config.set(key, &value);
+			}
+		}
+		fs::remove_dir_all(&dir).ok();
+
+		if let Err(err) = result {
+			panic::resume_unwind(err);
+		}
+	}
+
+	#[test]
+	fn update_description() {
+		with_local_repo(true, || {
+			let cache = new_cache!().unwrap();
+			cache.update(&mut AcquireProgress::quiet()).unwrap();
+
+			// The Release file doesn't list Translation-en,
+			// so update only fetched the summaries.
+			let cache = new_cache!().unwrap();
+			let cand = cache.get("translated-pkg").unwrap().candidate().unwrap();
+			assert!(!cand.description().unwrap().contains('\n'));
+
+			let mut progress = AcquireProgress::quiet();
+			let desc = cand.ensure_description(&mut progress).unwrap().unwrap();
+			assert_eq!(
+				desc,
+				"Rust FTW\n This is only used for testing.\n Why would you install this?"
+			);
+
+			// The Translation-en entry doesn't match its Description-md5.
+			let cand = cache.get("bad-md5").unwrap().candidate().unwrap();
+			assert!(cand.ensure_description(&mut progress).unwrap().is_none());
+		});
+	}
+
+	#[test]
+	fn update_release_info() {
+		with_local_repo(true, || {
+			let cache = new_cache!().unwrap();
+			cache.update(&mut AcquireProgress::quiet()).unwrap();
+
+			let cache = new_cache!().unwrap();
+			let repo = fs::canonicalize("tests/files/repo").unwrap();
+			let dist = format!("file:{}/./", repo.display());
+			let info = cache.release_info(&dist).unwrap();
+			assert_eq!(info.origin.as_deref(), Some("rust-apt"));
+			assert_eq!(info.suite.as_deref(), Some("rust-apt"));
+			assert_eq!(info.date.as_deref(), Some("Thu, 01 Jan 2026 00:00:00 UTC"));
+			assert!(info.components.is_empty());
+
+			// A prefix of the uri is not enough.
+			let parent = repo.parent().unwrap();
+			assert!(
+				cache
+					.release_info(&format!("file:{}", parent.display()))
+					.is_none()
+			);
+		});
+	}
+
+	#[test]
+	fn update_untrusted_changes() {
+		with_local_repo(false, || {
+			let cache = new_cache!().unwrap();
+			cache.update(&mut AcquireProgress::quiet()).unwrap();
+
+			let cache = new_cache!().unwrap();
+			let pkg = cache.get("translated-pkg").unwrap();
+			pkg.mark_install(true, true).unwrap();
+			assert!(!pkg.install_version().unwrap().is_trusted());
+
+			let untrusted = cache.has_untrusted_changes();
+			assert!(untrusted.iter().any(|pkg| pkg.name() == "translated-pkg"));
+		});
+	}
+
 	#[test]
 	fn install_and_remove() {
 		let cache = new_cache!().unwrap();
@@ -104,7 +270,7 @@ mod root {
 		let pkg = cache.get("neofetch").unwrap();
 
 		pkg.protect();
-		pkg.mark_install(true, true);
+		pkg.mark_install(true, true).unwrap();
 		cache.resolve(false).unwrap();
 		dbg!(pkg.marked_install());
 
@@ -118,7 +284,7 @@ mod root {
 		// I need to pick a better package. This removes my neofetch every time!
 		let pkg = cache.get("neofetch").unwrap();
 
-		pkg.mark_delete(true);
+		pkg.mark_delete(true).unwrap();
 
 		cache.commit(&mut progress, &mut inst_progress).unwrap();
 	}
@@ -134,8 +300,8 @@ mod root {
 		let pkg1 = cache.get("dep-pkg1").unwrap();
 		let pkg2 = cache.get("dep-pkg2").unwrap();
 
-		pkg1.mark_install(true, true);
-		pkg2.mark_install(true, true);
+		pkg1.mark_install(true, true).unwrap();
+		pkg2.mark_install(true, true).unwrap();
 		cache.resolve(false).unwrap();
 
 		let mut progress = AcquireProgress::apt();
@@ -150,9 +316,87 @@ mod root {
 		let pkg2 = cache.get("dep-pkg2").unwrap();
 
 		// Leave no trace
-		pkg1.mark_delete(true);
-		pkg2.mark_delete(true);
+		pkg1.mark_delete(true).unwrap();
+		pkg2.mark_delete(true).unwrap();
+
+		cache.commit(&mut progress, &mut inst_progress).unwrap();
+	}
+
+	#[test]
+	fn locally_installed() {
+		let deb = "tests/files/cache/conflict-pkg1_0.0.1.deb";
+		let cache = new_cache!(&[deb]).unwrap();
+		cache
+			.get("conflict-pkg1")
+			.unwrap()
+			.mark_install(true, true)
+			.unwrap();
+
+		let mut progress = AcquireProgress::apt();
+		let mut inst_progress = InstallProgress::apt();
+		cache.commit(&mut progress, &mut inst_progress).unwrap();
 
+		// Without the deb it only exists in the dpkg status file.
+		let cache = new_cache!().unwrap();
+		let local: Vec<String> = cache
+			.locally_installed()
+			.iter()
+			.map(|pkg| pkg.name().to_string())
+			.collect();
+
+		// Leave no trace before checking, so a failure doesn't either.
+		let cache = new_cache!(&[deb]).unwrap();
+		cache
+			.get("conflict-pkg1")
+			.unwrap()
+			.mark_delete(true)
+			.unwrap();
 		cache.commit(&mut progress, &mut inst_progress).unwrap();
+
+		assert!(local.iter().any(|name| name == "conflict-pkg1"));
+		// apt is available from the sources.
+		assert!(!local.iter().any(|name| name == "apt"));
+	}
+
+	#[test]
+	fn commit_lock_retry() {
+		// This test runs itself in a child process to hold the lock.
+		if std::env::var_os("RUST_APT_HOLD_LOCK").is_some() {
+			apt_lock().unwrap();
+			thread::sleep(Duration::from_secs(2));
+			apt_unlock();
+			return;
+		}
+
+		let mut child = Command::new(std::env::current_exe().unwrap())
+			.args(["--exact", "root::commit_lock_retry"])
+			.env("RUST_APT_HOLD_LOCK", "1")
+			.spawn()
+			.unwrap();
+
+		// Wait for the child to take the lock.
+		for _ in 0..100 {
+			if dpkg_is_locked() {
+				break;
+			}
+			thread::sleep(Duration::from_millis(50));
+		}
+		assert!(dpkg_is_locked());
+
+		let cache = new_cache!().unwrap();
+		let options = CommitOptions {
+			lock_retry: Some(LockRetry {
+				attempts: 20,
+				delay: Duration::from_millis(250),
+			}),
+		};
+
+		let mut progress = AcquireProgress::apt();
+		let mut inst_progress = InstallProgress::apt();
+		cache
+			.commit_with_options(&mut progress, &mut inst_progress, &options)
+			.unwrap();
+
+		assert!(child.wait().unwrap().success());
 	}
 }